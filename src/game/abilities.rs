@@ -0,0 +1,197 @@
+use super::{
+    components::{Facing, PhysicsBody, Player, Side, Velocity},
+    input::{Action, InputState},
+    physics::{Contacts, Impulse},
+    player::Jump,
+    resources::Time,
+};
+use bevy_ecs::{
+    change_detection::Res,
+    entity::Entity,
+    prelude::{Commands, Component, Query},
+    query::With,
+};
+use serde::Deserialize;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Ability {
+    DoubleJump,
+    AirDash,
+    GroundPound,
+    WallJump,
+}
+
+/// Configuração de uma habilidade
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct AbilitySlot {
+    pub binding: Action,
+    pub cooldown_millis: u64,
+    #[serde(skip)]
+    cooldown: Duration,
+    /// Habilidades aéreas só podem ser usadas uma vez antes de voltar para o chão
+    #[serde(skip)]
+    spent: bool,
+}
+
+impl AbilitySlot {
+    pub fn new(binding: Action, cooldown_millis: u64) -> Self {
+        Self {
+            binding,
+            cooldown_millis,
+            cooldown: Duration::ZERO,
+            spent: false,
+        }
+    }
+
+    fn ready(&self) -> bool {
+        self.cooldown.is_zero() && !self.spent
+    }
+
+    fn use_slot(&mut self) {
+        self.cooldown = Duration::from_millis(self.cooldown_millis);
+        self.spent = true;
+    }
+}
+
+/// Movimentos que o player já desbloqueou e como eles são usados
+#[derive(Debug, Component, Deserialize)]
+#[serde(default)]
+pub struct Abilities {
+    pub unlocked: Vec<Ability>,
+    pub double_jump: AbilitySlot,
+    pub air_dash: AbilitySlot,
+    pub ground_pound: AbilitySlot,
+    /// Pode passar do `max_horizontal_speed` do [PhysicsBody], que só volta a valer depois de
+    /// `dash_millis`
+    pub dash_speed: f64,
+    pub dash_millis: u64,
+    pub pound_speed: f64,
+}
+
+impl Default for Abilities {
+    fn default() -> Self {
+        Self {
+            unlocked: Vec::new(),
+            double_jump: AbilitySlot::new(Action::Up, 0),
+            air_dash: AbilitySlot::new(Action::Dash, 600),
+            ground_pound: AbilitySlot::new(Action::Pound, 300),
+            dash_speed: 1400.0,
+            dash_millis: 200,
+            pound_speed: 1500.0,
+        }
+    }
+}
+
+impl Abilities {
+    pub fn has(&self, ability: Ability) -> bool {
+        self.unlocked.contains(&ability)
+    }
+
+    pub fn unlock(&mut self, ability: Ability) {
+        if !self.has(ability) {
+            self.unlocked.push(ability);
+        }
+    }
+
+    /// O pulo na parede não tem slot, ele usa o pulo normal e é configurado em
+    /// [WallJump](super::player::WallJump)
+    fn slot_mut(&mut self, ability: Ability) -> Option<&mut AbilitySlot> {
+        match ability {
+            Ability::DoubleJump => Some(&mut self.double_jump),
+            Ability::AirDash => Some(&mut self.air_dash),
+            Ability::GroundPound => Some(&mut self.ground_pound),
+            Ability::WallJump => None,
+        }
+    }
+
+    fn slots_mut(&mut self) -> [&mut AbilitySlot; 3] {
+        [
+            &mut self.double_jump,
+            &mut self.air_dash,
+            &mut self.ground_pound,
+        ]
+    }
+
+    /// Retorna se a habilidade pode ser usada agora e, se puder, começa o cooldown dela
+    ///
+    /// `pressed` recebe o binding da habilidade e diz se ele foi apertado nesse frame
+    fn try_use(&mut self, ability: Ability, pressed: impl FnOnce(Action) -> bool) -> bool {
+        if !self.has(ability) {
+            return false;
+        }
+        let Some(slot) = self.slot_mut(ability) else {
+            return false;
+        };
+        if !slot.ready() || !pressed(slot.binding) {
+            return false;
+        }
+        slot.use_slot();
+        true
+    }
+}
+
+type AbilityUsers<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut Abilities,
+        &'static mut Velocity,
+        &'static mut Jump,
+        &'static Contacts,
+        &'static Facing,
+        &'static PhysicsBody,
+    ),
+    With<Player>,
+>;
+
+pub fn use_abilities(
+    mut query: AbilityUsers,
+    inputs: Res<InputState>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let just_pressed = |action: Action| inputs.just_pressed(action);
+
+    for (entity, mut abilities, mut velocity, mut jump, contacts, facing, body) in query.iter_mut()
+    {
+        for slot in abilities.slots_mut() {
+            slot.cooldown = slot.cooldown.saturating_sub(time.delta());
+            if contacts.ground {
+                slot.spent = false;
+            }
+        }
+        if contacts.ground {
+            continue;
+        }
+
+        // Um pulo apertado no ar que não foi usado para pular do chão nem da parede fica no
+        // buffer do Jump, então com o binding padrão o pulo duplo usa ele
+        let double_jump = abilities.try_use(Ability::DoubleJump, |binding| match binding {
+            Action::Up => jump.take_buffered(),
+            binding => just_pressed(binding),
+        });
+        if double_jump {
+            jump.start(&mut velocity, body);
+        }
+
+        if abilities.try_use(Ability::AirDash, just_pressed) {
+            let direction = match facing.0 {
+                Side::Left => -1.0,
+                Side::Right => 1.0,
+            };
+            velocity.x = direction * abilities.dash_speed;
+            velocity.y = 0.0;
+            commands
+                .entity(entity)
+                .insert(Impulse::from_millis(abilities.dash_millis));
+        }
+
+        if abilities.try_use(Ability::GroundPound, just_pressed) {
+            velocity.x = 0.0;
+            velocity.y = -abilities.pound_speed;
+        }
+    }
+}
@@ -0,0 +1,300 @@
+use super::input::{Action, ActionState, InputEvent};
+use bevy_ecs::system::Resource;
+use log::{error, info};
+use sdl2::{
+    event::Event,
+    keyboard::{Keycode, Mod},
+};
+use serde::Deserialize;
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+/// Arquivo de bindings usado quando a variável `KEY_BINDINGS` não está definida
+pub const DEFAULT_BINDINGS_PATH: &str = "assets/bindings.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modifier {
+    Ctrl,
+    Shift,
+    Alt,
+}
+
+impl Modifier {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "ctrl" | "control" => Some(Self::Ctrl),
+            "shift" => Some(Self::Shift),
+            "alt" => Some(Self::Alt),
+            _ => None,
+        }
+    }
+
+    fn held(&self, keymod: Mod) -> bool {
+        let mask = match self {
+            Modifier::Ctrl => Mod::LCTRLMOD | Mod::RCTRLMOD,
+            Modifier::Shift => Mod::LSHIFTMOD | Mod::RSHIFTMOD,
+            Modifier::Alt => Mod::LALTMOD | Mod::RALTMOD,
+        };
+        keymod.intersects(mask)
+    }
+}
+
+/// Separa os modificadores do nome da tecla, ex: `"Ctrl+Shift+A"` vira `([Ctrl, Shift], "A")`
+///
+/// Só tira o que for um modificador conhecido, então teclas como `"Keypad +"` continuam inteiras
+fn split_modifiers(combo: &str) -> (Vec<Modifier>, &str) {
+    let mut modifiers = Vec::new();
+    let mut rest = combo.trim();
+    while let Some((prefix, key)) = rest.split_once('+') {
+        let Some(modifier) = Modifier::from_name(prefix.trim()) else {
+            break;
+        };
+        modifiers.push(modifier);
+        rest = key.trim();
+    }
+    (modifiers, rest)
+}
+
+/// Uma tecla e os modificadores que precisam estar apertados junto com ela. No arquivo é o nome
+/// da tecla no SDL, com os modificadores antes: `"Left"`, `"W"`, `"Ctrl+A"`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct KeyCombo {
+    pub key: Keycode,
+    pub modifiers: Vec<Modifier>,
+}
+
+impl KeyCombo {
+    pub fn key(key: Keycode) -> Self {
+        Self {
+            key,
+            modifiers: Vec::new(),
+        }
+    }
+
+    fn held(&self, keymod: Mod) -> bool {
+        self.modifiers.iter().all(|m| m.held(keymod))
+    }
+}
+
+impl TryFrom<String> for KeyCombo {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let (modifiers, name) = split_modifiers(&value);
+        let key = Keycode::from_name(name).ok_or_else(|| format!("Unknown key {name:?}"))?;
+        Ok(Self { key, modifiers })
+    }
+}
+
+/// Quais teclas disparam cada [Action]. Uma ação pode ter várias teclas
+#[derive(Debug, Resource)]
+pub struct KeyBindings {
+    bindings: Vec<(KeyCombo, Action)>,
+    /// Teclas apertadas agora de cada ação, ela só é solta junto com a última
+    pressed: HashMap<Action, HashSet<Keycode>>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let bindings = [
+            (Keycode::Left, Action::Left),
+            (Keycode::Right, Action::Right),
+            (Keycode::Down, Action::Down),
+            (Keycode::Up, Action::Up),
+            (Keycode::A, Action::Attack),
+            (Keycode::S, Action::Dash),
+            (Keycode::D, Action::Pound),
+            (Keycode::Space, Action::Jump),
+            (Keycode::F, Action::Detonate),
+            (Keycode::G, Action::Grapple),
+        ];
+        Self {
+            bindings: bindings
+                .into_iter()
+                .map(|(key, action)| (KeyCombo::key(key), action))
+                .collect(),
+            pressed: HashMap::new(),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Lê as teclas de um JSON como `{ "up": ["Up", "W"], "attack": ["Ctrl+A"] }`
+    ///
+    /// Ações que não aparecem no arquivo ficam com as teclas padrão que o arquivo não usou, e sem
+    /// arquivo todas ficam
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let Ok(file) = std::fs::read_to_string(path) else {
+            info!("No key bindings at {}, using defaults", path.display());
+            return Self::default();
+        };
+        match serde_json::from_str::<HashMap<Action, Vec<KeyCombo>>>(&file) {
+            Ok(configured) => Self::default().with(configured),
+            Err(err) => {
+                error!("Invalid key bindings at {}: {err}", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    /// Troca as teclas das ações em `configured`. Uma tecla configurada deixa de disparar o que
+    /// ela fazia por padrão, senão com WASD o `A` andaria e atacaria junto
+    fn with(mut self, configured: HashMap<Action, Vec<KeyCombo>>) -> Self {
+        let taken: Vec<_> = configured.values().flatten().cloned().collect();
+        self.bindings
+            .retain(|(combo, action)| !configured.contains_key(action) && !taken.contains(combo));
+        for (action, combos) in configured {
+            self.bindings
+                .extend(combos.into_iter().map(|combo| (combo, action)));
+        }
+        self
+    }
+
+    /// Converte um evento de teclado nos [InputEvent]s das ações ligadas a ele
+    ///
+    /// Apertando, só valem os bindings com mais modificadores entre os que estão apertados, assim
+    /// `Ctrl+A` não dispara também o que está em `A`. Soltando, a tecla solta as ações que não
+    /// têm mais nenhuma outra tecla apertada
+    pub fn input_events(&mut self, event: &Event) -> Vec<InputEvent> {
+        match *event {
+            Event::KeyDown {
+                keycode: Some(key),
+                keymod,
+                ..
+            } => {
+                let matching: Vec<_> = self
+                    .bindings
+                    .iter()
+                    .filter(|(combo, _)| combo.key == key && combo.held(keymod))
+                    .collect();
+                let most_specific = matching
+                    .iter()
+                    .map(|(combo, _)| combo.modifiers.len())
+                    .max();
+                let actions: Vec<_> = matching
+                    .iter()
+                    .filter(|(combo, _)| Some(combo.modifiers.len()) == most_specific)
+                    .map(|(_, action)| *action)
+                    .collect();
+                for action in &actions {
+                    self.pressed.entry(*action).or_default().insert(key);
+                }
+                actions
+                    .into_iter()
+                    .map(|action| InputEvent::new(action, ActionState::Active))
+                    .collect()
+            }
+            Event::KeyUp {
+                keycode: Some(key), ..
+            } => {
+                let actions: HashSet<_> = self
+                    .bindings
+                    .iter()
+                    .filter(|(combo, _)| combo.key == key)
+                    .map(|(_, action)| *action)
+                    .collect();
+                actions
+                    .into_iter()
+                    .filter(|action| {
+                        let keys = self.pressed.entry(*action).or_default();
+                        keys.remove(&key);
+                        keys.is_empty()
+                    })
+                    .map(|action| InputEvent::new(action, ActionState::Inactive))
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_modifiers_from_key() {
+        assert_eq!(
+            split_modifiers("Ctrl+Shift+A"),
+            (vec![Modifier::Ctrl, Modifier::Shift], "A")
+        );
+        assert_eq!(split_modifiers("alt + Left"), (vec![Modifier::Alt], "Left"));
+        assert_eq!(split_modifiers("Keypad +"), (vec![], "Keypad +"));
+        assert_eq!(split_modifiers("W"), (vec![], "W"));
+    }
+
+    fn key_event(key: Keycode, down: bool) -> Event {
+        if down {
+            Event::KeyDown {
+                timestamp: 0,
+                window_id: 0,
+                keycode: Some(key),
+                scancode: None,
+                keymod: Mod::NOMOD,
+                repeat: false,
+            }
+        } else {
+            Event::KeyUp {
+                timestamp: 0,
+                window_id: 0,
+                keycode: Some(key),
+                scancode: None,
+                keymod: Mod::NOMOD,
+                repeat: false,
+            }
+        }
+    }
+
+    #[test]
+    fn configured_keys_replace_their_defaults() {
+        let mut bindings = KeyBindings::default().with(HashMap::from([
+            (Action::Up, vec![KeyCombo::key(Keycode::W)]),
+            (Action::Left, vec![KeyCombo::key(Keycode::A)]),
+            (Action::Down, vec![KeyCombo::key(Keycode::S)]),
+            (Action::Right, vec![KeyCombo::key(Keycode::D)]),
+        ]));
+
+        assert_eq!(
+            bindings.input_events(&key_event(Keycode::A, true)),
+            vec![InputEvent::new(Action::Left, ActionState::Active)]
+        );
+        assert_eq!(
+            bindings.input_events(&key_event(Keycode::S, true)),
+            vec![InputEvent::new(Action::Down, ActionState::Active)]
+        );
+        // Teclas padrão que não foram reaproveitadas continuam valendo
+        assert_eq!(
+            bindings.input_events(&key_event(Keycode::Space, true)),
+            vec![InputEvent::new(Action::Jump, ActionState::Active)]
+        );
+        assert_eq!(
+            bindings.input_events(&key_event(Keycode::Left, true)),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn action_stays_active_while_any_key_is_held() {
+        let mut bindings = KeyBindings::default().with(HashMap::from([(
+            Action::Right,
+            vec![KeyCombo::key(Keycode::Right), KeyCombo::key(Keycode::L)],
+        )]));
+        let active = vec![InputEvent::new(Action::Right, ActionState::Active)];
+        let inactive = vec![InputEvent::new(Action::Right, ActionState::Inactive)];
+
+        assert_eq!(
+            bindings.input_events(&key_event(Keycode::Right, true)),
+            active
+        );
+        assert_eq!(bindings.input_events(&key_event(Keycode::L, true)), active);
+        assert_eq!(bindings.input_events(&key_event(Keycode::L, false)), vec![]);
+        assert_eq!(
+            bindings.input_events(&key_event(Keycode::Right, false)),
+            inactive
+        );
+    }
+}
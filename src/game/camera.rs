@@ -1,6 +1,10 @@
-use crate::game::components::{
-    hitbox::{HitboxBorrowedMut, RectInPosition, ToHitbox},
-    CollisionAxis, ColorDrawType, Colorable, Hitbox, Player, Position, Rectangle,
+use crate::game::{
+    components::{
+        hitbox::{HitboxBorrowedMut, RectInPosition, ToHitbox},
+        CollisionAxis, CollisionLayers, ColorDrawType, Colorable, Hitbox, Player, Position,
+        Rectangle,
+    },
+    render::Screen,
 };
 use bevy_ecs::{
     component::Component,
@@ -8,7 +12,7 @@ use bevy_ecs::{
     system::{Query, ResMut, Resource},
     world::{FromWorld, World},
 };
-use sdl2::{pixels::Color, render::WindowCanvas};
+use sdl2::pixels::Color;
 
 #[derive(Component)]
 pub struct CameraHitbox;
@@ -27,10 +31,10 @@ impl Camera {
 
 impl FromWorld for Camera {
     fn from_world(world: &mut World) -> Self {
-        let (w, h) = world
-            .non_send_resource_mut::<WindowCanvas>()
-            .window()
-            .size();
+        let Screen {
+            width: w,
+            height: h,
+        } = *world.resource::<Screen>();
 
         world.spawn((
             CameraHitbox,
@@ -41,6 +45,7 @@ impl FromWorld for Camera {
                 left: 30.0,
             }
             .into_bundle((w, h)),
+            CollisionLayers::NONE,
             Colorable::new(Color::GREEN, ColorDrawType::Outline),
         ));
 
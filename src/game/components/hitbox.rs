@@ -1,11 +1,11 @@
-use super::{Position, Rectangle, Velocity};
-use bevy_ecs::{change_detection::Mut, entity::Entity};
+use super::{Position, Rectangle};
+use bevy_ecs::entity::Entity;
 use std::{
     cmp::Ordering::{Equal, Greater, Less},
     ops::{Deref, DerefMut},
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CollisionAxis {
     Up,
     Down,
@@ -13,6 +13,17 @@ pub enum CollisionAxis {
     Right,
 }
 
+impl CollisionAxis {
+    pub fn opposite(self) -> Self {
+        match self {
+            CollisionAxis::Up => CollisionAxis::Down,
+            CollisionAxis::Down => CollisionAxis::Up,
+            CollisionAxis::Left => CollisionAxis::Right,
+            CollisionAxis::Right => CollisionAxis::Left,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Hitbox<T>(T);
 
@@ -99,39 +110,6 @@ impl<T: RectInPosition> Hitbox<T> {
     }
 }
 
-#[derive(Debug)]
-pub struct HitboxOwned<'a> {
-    pub pos: Mut<'a, Position>,
-    pub rect: &'a Rectangle,
-}
-
-impl<'a> RectInPosition for HitboxOwned<'a> {
-    fn pos(&self) -> &Position {
-        &self.pos
-    }
-
-    fn rect(&self) -> &Rectangle {
-        &self.rect
-    }
-}
-
-#[derive(Debug)]
-pub struct HitboxOwnedWithVelocity<'a> {
-    pub pos: Mut<'a, Position>,
-    pub rect: &'a Rectangle,
-    pub velocity: Mut<'a, Velocity>,
-}
-
-impl<'a> RectInPosition for HitboxOwnedWithVelocity<'a> {
-    fn pos(&self) -> &Position {
-        &self.pos
-    }
-
-    fn rect(&self) -> &Rectangle {
-        &self.rect
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct HitboxBorrowed<'a> {
     pub pos: &'a Position,
@@ -168,14 +146,6 @@ pub trait ToHitbox<T> {
     fn hitbox(&self) -> Hitbox<T>;
 }
 
-pub trait ToHitboxMut<'a> {
-    fn hitbox_mut(&'a mut self) -> Hitbox<HitboxBorrowedMut<'a>>;
-}
-
-pub trait IntoHitbox<T> {
-    fn into_hitbox(self) -> Hitbox<T>;
-}
-
 impl<'a> ToHitbox<HitboxBorrowed<'a>> for (&'a Position, &'a Rectangle) {
     fn hitbox(&self) -> Hitbox<HitboxBorrowed<'a>> {
         let (pos, rect) = *self;
@@ -183,33 +153,6 @@ impl<'a> ToHitbox<HitboxBorrowed<'a>> for (&'a Position, &'a Rectangle) {
     }
 }
 
-impl<'a> ToHitboxMut<'a> for (Mut<'a, Position>, &'a Rectangle) {
-    fn hitbox_mut(&'a mut self) -> Hitbox<HitboxBorrowedMut<'a>> {
-        let (ref mut pos, rect) = self;
-        Hitbox(HitboxBorrowedMut { pos, rect })
-    }
-}
-
-impl<'a> IntoHitbox<HitboxOwned<'a>> for (Mut<'a, Position>, &'a Rectangle) {
-    fn into_hitbox(self) -> Hitbox<HitboxOwned<'a>> {
-        let (pos, rect) = self;
-        Hitbox(HitboxOwned { pos, rect })
-    }
-}
-
-impl<'a> IntoHitbox<HitboxOwnedWithVelocity<'a>>
-    for (Mut<'a, Position>, &'a Rectangle, Mut<'a, Velocity>)
-{
-    fn into_hitbox(self) -> Hitbox<HitboxOwnedWithVelocity<'a>> {
-        let (pos, rect, velocity) = self;
-        Hitbox(HitboxOwnedWithVelocity {
-            pos,
-            rect,
-            velocity,
-        })
-    }
-}
-
 impl<'a> ToHitbox<HitboxBorrowed<'a>> for (Entity, &'a Position, &'a Rectangle) {
     fn hitbox(&self) -> Hitbox<HitboxBorrowed<'a>> {
         let (_, pos, rect) = *self;
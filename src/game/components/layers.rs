@@ -0,0 +1,133 @@
+use bevy_ecs::component::Component;
+use serde::Deserialize;
+use std::ops::BitOr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Layer {
+    Player,
+    Enemy,
+    Bullet,
+    Pickup,
+    Hazard,
+    Terrain,
+    Prop,
+    Sensor,
+}
+
+/// Conjunto de [Layer]s. No mapa é uma lista de nomes, ex: `["player", "enemy"]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(from = "Vec<Layer>")]
+pub struct Layers(u32);
+
+impl Layers {
+    pub const NONE: Self = Self(0);
+    pub const ALL: Self = Self(u32::MAX);
+
+    pub const fn of(layer: Layer) -> Self {
+        Self(1 << layer as u32)
+    }
+
+    pub const fn without(self, layer: Layer) -> Self {
+        Self(self.0 & !Self::of(layer).0)
+    }
+
+    pub fn intersects(&self, other: Layers) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl Default for Layers {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl From<Layer> for Layers {
+    fn from(layer: Layer) -> Self {
+        Self::of(layer)
+    }
+}
+
+impl From<Vec<Layer>> for Layers {
+    fn from(layers: Vec<Layer>) -> Self {
+        layers
+            .into_iter()
+            .fold(Self::NONE, |acc, layer| acc | Self::of(layer))
+    }
+}
+
+impl<T: Into<Layers>> BitOr<T> for Layers {
+    type Output = Self;
+
+    fn bitor(self, rhs: T) -> Self::Output {
+        Self(self.0 | rhs.into().0)
+    }
+}
+
+/// Em quais camadas uma entidade está (`membership`) e com quais ela interage (`mask`)
+///
+/// Duas entidades só colidem se cada uma estiver na máscara da outra. Entidades sem esse
+/// componente estão em todas as camadas e interagem com todas
+#[derive(Debug, Clone, Copy, Component, PartialEq, Eq)]
+pub struct CollisionLayers {
+    pub membership: Layers,
+    pub mask: Layers,
+}
+
+impl CollisionLayers {
+    pub const ALL: Self = Self::new(Layers::ALL, Layers::ALL);
+    /// Não interage com nada, usado por coisas que têm hitbox mas não fazem parte do jogo
+    pub const NONE: Self = Self::new(Layers::NONE, Layers::NONE);
+
+    pub const fn new(membership: Layers, mask: Layers) -> Self {
+        Self { membership, mask }
+    }
+
+    pub fn interacts_with(&self, other: &CollisionLayers) -> bool {
+        self.mask.intersects(other.membership) && other.mask.intersects(self.membership)
+    }
+
+    /// Mesmo que [CollisionLayers::interacts_with], tratando a falta do componente como
+    /// [CollisionLayers::ALL]
+    pub fn interacts(a: Option<&CollisionLayers>, b: Option<&CollisionLayers>) -> bool {
+        a.unwrap_or(&Self::ALL)
+            .interacts_with(b.unwrap_or(&Self::ALL))
+    }
+}
+
+impl Default for CollisionLayers {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layers_from_names() {
+        let layers: Layers = serde_json::from_str(r#"["player", "enemy"]"#).unwrap();
+        assert_eq!(layers, Layers::of(Layer::Player) | Layer::Enemy);
+        assert!(!layers.intersects(Layer::Pickup.into()));
+    }
+
+    #[test]
+    fn interaction_needs_both_masks() {
+        let player = CollisionLayers::new(Layer::Player.into(), Layers::ALL);
+        let bullet = CollisionLayers::new(Layer::Bullet.into(), Layers::ALL.without(Layer::Player));
+        let coin = CollisionLayers::new(Layer::Pickup.into(), Layer::Player.into());
+        let enemy = CollisionLayers::new(Layer::Enemy.into(), Layers::ALL);
+
+        assert!(!player.interacts_with(&bullet));
+        assert!(!bullet.interacts_with(&player));
+        assert!(player.interacts_with(&coin));
+        assert!(!enemy.interacts_with(&coin));
+        assert!(CollisionLayers::interacts(None, Some(&enemy)));
+        assert!(!CollisionLayers::interacts(
+            Some(&CollisionLayers::NONE),
+            None
+        ));
+    }
+}
@@ -1,12 +1,20 @@
 pub mod hitbox;
-
-use crate::game::components::hitbox::{HitboxBorrowed, RectInPosition};
+pub mod layers;
+pub mod shape;
+
+use crate::game::{
+    abilities::Ability,
+    components::hitbox::{HitboxBorrowed, RectInPosition},
+    explosion::Explosive,
+};
 use bevy_ecs::{bundle::Bundle, prelude::Component};
 use enum_map::EnumMap;
 use hitbox::HitboxBorrowedMut;
 pub use hitbox::{CollisionAxis, Hitbox};
+pub use layers::{CollisionLayers, Layer, Layers};
 use sdl2::pixels::Color;
 use serde::Deserialize;
+pub use shape::{Shape, Side};
 
 #[derive(Component)]
 pub struct Player;
@@ -19,13 +27,16 @@ pub struct BulletBundle {
     pub marker: Bullet,
     pub position: Position,
     pub velocity: Velocity,
+    pub body: PhysicsBody,
     pub rectangle: Rectangle,
     pub solid: Solid,
+    pub layers: CollisionLayers,
     pub color: Colorable,
     pub bounce: Bounce,
+    pub explosive: Explosive,
 }
 
-#[derive(Debug, Component, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Default, Component, Clone, PartialEq, Deserialize)]
 pub struct Position {
     pub x: f64,
     pub y: f64,
@@ -40,6 +51,7 @@ impl Position {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
 pub struct Normal {
     x: f64,
     y: f64,
@@ -82,8 +94,89 @@ impl Velocity {
     }
 }
 
-#[derive(Debug, Component)]
-pub struct Gravitable;
+/// Entidades afetadas pela gravidade
+///
+/// `scale` e `max_fall_speed` são controlados pelos [Volume](crate::game::volume::Volume)s em que
+/// a entidade está
+#[derive(Debug, Clone, Copy, Component, PartialEq)]
+pub struct Gravitable {
+    /// Multiplica a gravidade do [PhysicsBody], negativo inverte a direção
+    pub scale: f64,
+    /// Escala fora de qualquer volume, que fica até outro
+    /// [TriggerAction::SetGravity](crate::game::trigger::TriggerAction::SetGravity) mudar
+    pub base_scale: f64,
+    /// Velocidade máxima de queda, além da do [PhysicsBody]
+    pub max_fall_speed: Option<f64>,
+}
+
+impl Default for Gravitable {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            base_scale: 1.0,
+            max_fall_speed: None,
+        }
+    }
+}
+
+/// Para que lado a entidade está virada
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Facing(pub Side);
+
+impl Default for Facing {
+    fn default() -> Self {
+        Self(Side::Right)
+    }
+}
+
+/// Parâmetros de física de uma entidade que se move
+///
+/// Cada entidade com [Velocity] tem o seu, assim balas, inimigos e caixas não ficam presos aos
+/// limites do player. Todas as velocidades e acelerações são em pixels por segundo
+#[derive(Debug, Clone, Copy, Component, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PhysicsBody {
+    /// Multiplicador da gravidade global
+    pub gravity_scale: f64,
+    pub max_horizontal_speed: f64,
+    pub max_vertical_speed: f64,
+    /// Aceleração horizontal de quando a entidade anda sozinha, por segundo²
+    pub acceleration: f64,
+    /// Fração da velocidade perdida por segundo
+    pub drag: f64,
+}
+
+impl PhysicsBody {
+    pub const PLAYER: Self = Self {
+        gravity_scale: 1.0,
+        max_horizontal_speed: 900.0,
+        max_vertical_speed: 1500.0,
+        acceleration: 3600.0,
+        drag: 0.0,
+    };
+
+    pub const BULLET: Self = Self {
+        gravity_scale: 0.0,
+        max_horizontal_speed: 3000.0,
+        max_vertical_speed: 3000.0,
+        acceleration: 0.0,
+        drag: 0.0,
+    };
+
+    pub const CRATE: Self = Self {
+        gravity_scale: 1.0,
+        max_horizontal_speed: 1500.0,
+        max_vertical_speed: 1500.0,
+        acceleration: 0.0,
+        drag: 0.0,
+    };
+}
+
+impl Default for PhysicsBody {
+    fn default() -> Self {
+        Self::PLAYER
+    }
+}
 
 #[derive(Debug, Clone, Copy, Component, PartialEq, Deserialize)]
 pub struct Rectangle {
@@ -159,6 +252,7 @@ impl Colorable {
 pub enum CoinKind {
     Color(Color),
     Jump(u32),
+    Ability(Ability),
 }
 
 #[derive(Debug, enum_map::Enum, Deserialize, PartialEq)]
@@ -175,6 +269,20 @@ pub struct Solid {
     sides: EnumMap<Direction, bool>,
 }
 
+/// Entidade com velocidade que outras conseguem empurrar, como uma caixa
+#[derive(Debug, Clone, Copy, Component, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Pushable {
+    /// Desaceleração horizontal no chão, em pixels por segundo²
+    pub friction: f64,
+}
+
+impl Default for Pushable {
+    fn default() -> Self {
+        Self { friction: 3000.0 }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Component)]
 pub struct Bounce {
     pub enabled: bool,
@@ -204,6 +312,28 @@ impl Solid {
 #[derive(Debug, Component)]
 pub struct KillZone;
 
+#[derive(Debug, Clone, Copy, Component, PartialEq, Eq)]
+pub struct Health {
+    pub current: u32,
+    pub max: u32,
+}
+
+impl Health {
+    pub fn new(max: u32) -> Self {
+        Self { current: max, max }
+    }
+
+    pub fn heal(&mut self, amount: u32) {
+        self.current = self.current.saturating_add(amount).min(self.max);
+    }
+
+    /// Retorna `true` se a vida chegou a zero
+    pub fn damage(&mut self, amount: u32) -> bool {
+        self.current = self.current.saturating_sub(amount);
+        self.current == 0
+    }
+}
+
 #[derive(Debug, Component)]
 pub struct InfiniteArea {
     pub start: f64,
@@ -0,0 +1,207 @@
+use super::{hitbox::RectInPosition, Hitbox, Normal};
+use bevy_ecs::component::Component;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// Forma de uma entidade estática que não é o retângulo inteiro
+///
+/// A forma sempre fica dentro do [Rectangle](super::Rectangle) da entidade, que continua sendo a
+/// bounding box usada para desenhar e para descartar colisões rapidamente
+#[derive(Debug, Clone, Copy, Component, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Shape {
+    /// Triângulo retângulo com o ângulo reto embaixo do lado `high`
+    Slope { high: Side },
+    /// Círculo inscrito no retângulo
+    Circle,
+}
+
+/// Resultado da colisão de uma hitbox com uma [Shape]
+#[derive(Debug)]
+pub struct Contact {
+    /// Deslocamento que tira a hitbox de dentro da forma
+    pub push: (f64, f64),
+    /// Normal da superfície tocada, apontando para fora da forma
+    pub normal: Normal,
+}
+
+impl Shape {
+    pub fn contact<S: RectInPosition, M: RectInPosition>(
+        &self,
+        shape: &Hitbox<S>,
+        mover: &Hitbox<M>,
+    ) -> Option<Contact> {
+        if !mover.colides_with(shape) {
+            return None;
+        }
+        match self {
+            Shape::Slope { high } => Self::slope_contact(*high, shape, mover),
+            Shape::Circle => Self::circle_contact(shape, mover),
+        }
+    }
+
+    /// Distância vertical entre a base de `mover` e a superfície da rampa embaixo dela
+    pub fn gap_below<S: RectInPosition, M: RectInPosition>(
+        &self,
+        shape: &Hitbox<S>,
+        mover: &Hitbox<M>,
+    ) -> Option<f64> {
+        match self {
+            Shape::Slope { high } => {
+                if mover.right() <= shape.left() || mover.left() >= shape.right() {
+                    return None;
+                }
+                Some(mover.bottom() - Self::slope_height(*high, shape, mover))
+            }
+            Shape::Circle => None,
+        }
+    }
+
+    /// Normal da superfície inclinada de uma rampa
+    pub fn slope_normal<S: RectInPosition>(high: Side, shape: &Hitbox<S>) -> Normal {
+        let (w, h) = (shape.rect().width as f64, shape.rect().height as f64);
+        match high {
+            Side::Right => Normal::new(-h, w),
+            Side::Left => Normal::new(h, w),
+        }
+    }
+
+    /// Altura da rampa no ponto em que o canto de `mover` encosta nela
+    fn slope_height<S: RectInPosition, M: RectInPosition>(
+        high: Side,
+        shape: &Hitbox<S>,
+        mover: &Hitbox<M>,
+    ) -> f64 {
+        let (w, h) = (shape.rect().width as f64, shape.rect().height as f64);
+        let ratio = match high {
+            Side::Right => (mover.right().min(shape.right()) - shape.left()) / w,
+            Side::Left => (shape.right() - mover.left().max(shape.left())) / w,
+        };
+        shape.bottom() + h * ratio
+    }
+
+    fn slope_contact<S: RectInPosition, M: RectInPosition>(
+        high: Side,
+        shape: &Hitbox<S>,
+        mover: &Hitbox<M>,
+    ) -> Option<Contact> {
+        let up = Self::slope_height(high, shape, mover) - mover.bottom();
+        if up <= 0.0 {
+            return None;
+        }
+        let down = mover.top() - shape.bottom();
+        let side = match high {
+            Side::Right => shape.right() - mover.left(),
+            Side::Left => mover.right() - shape.left(),
+        };
+
+        // A rampa sempre empurra para cima, assim andar nela não faz o player deslizar
+        let contact = if up <= down && up <= side {
+            Contact {
+                push: (0.0, up),
+                normal: Self::slope_normal(high, shape),
+            }
+        } else if down <= side {
+            Contact {
+                push: (0.0, -down),
+                normal: Normal::new(0.0, -1.0),
+            }
+        } else {
+            match high {
+                Side::Right => Contact {
+                    push: (side, 0.0),
+                    normal: Normal::new(1.0, 0.0),
+                },
+                Side::Left => Contact {
+                    push: (-side, 0.0),
+                    normal: Normal::new(-1.0, 0.0),
+                },
+            }
+        };
+        Some(contact)
+    }
+
+    fn circle_contact<S: RectInPosition, M: RectInPosition>(
+        shape: &Hitbox<S>,
+        mover: &Hitbox<M>,
+    ) -> Option<Contact> {
+        let center = shape.center();
+        let radius = shape.rect().width.min(shape.rect().height) as f64 / 2.0;
+        let closest_x = center.x.clamp(mover.left(), mover.right());
+        let closest_y = center.y.clamp(mover.bottom(), mover.top());
+        let (dx, dy) = (closest_x - center.x, closest_y - center.y);
+        let distance = (dx.powi(2) + dy.powi(2)).sqrt();
+        if distance >= radius {
+            return None;
+        }
+        if distance == 0.0 {
+            // O centro do círculo está dentro da hitbox, então joga ela para cima
+            return Some(Contact {
+                push: (0.0, center.y + radius - mover.bottom()),
+                normal: Normal::new(0.0, 1.0),
+            });
+        }
+
+        let normal = Normal::new(dx, dy);
+        let depth = radius - distance;
+        Some(Contact {
+            push: (normal.x() * depth, normal.y() * depth),
+            normal,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::components::{Position, Rectangle};
+
+    #[test]
+    fn slope_pushes_up_to_surface() {
+        let slope = Shape::Slope { high: Side::Right };
+        let (slope_pos, slope_rect) = (Position::new(0, 0), Rectangle::new(100, 100));
+        let (pos, rect) = (Position::new(10, 55), Rectangle::new(50, 50));
+
+        let contact = slope
+            .contact(&slope_rect.on_position(&slope_pos), &rect.on_position(&pos))
+            .expect("hitbox is inside the slope");
+        assert_eq!(contact.push, (0.0, 5.0));
+        assert!(contact.normal.x() < 0.0 && contact.normal.y() > 0.0);
+    }
+
+    #[test]
+    fn slope_ignores_empty_half() {
+        let slope = Shape::Slope { high: Side::Left };
+        let (slope_pos, slope_rect) = (Position::new(0, 0), Rectangle::new(100, 100));
+        let (pos, rect) = (Position::new(60, 50), Rectangle::new(20, 20));
+
+        let slope_hitbox = slope_rect.on_position(&slope_pos);
+        let hitbox = rect.on_position(&pos);
+        assert!(slope.contact(&slope_hitbox, &hitbox).is_none());
+        assert_eq!(slope.gap_below(&slope_hitbox, &hitbox), Some(10.0));
+    }
+
+    #[test]
+    fn circle_normal_points_to_hitbox() {
+        let circle = Shape::Circle;
+        let (circle_pos, circle_rect) = (Position::new(0, 0), Rectangle::new(100, 100));
+        let (pos, rect) = (Position::new(80, 80), Rectangle::new(20, 20));
+
+        let contact = circle
+            .contact(
+                &circle_rect.on_position(&circle_pos),
+                &rect.on_position(&pos),
+            )
+            .expect("hitbox touches the circle");
+        let diagonal = 1.0 / 2.0f64.sqrt();
+        assert!((contact.normal.x() - diagonal).abs() < 1e-9);
+        assert!((contact.normal.y() - diagonal).abs() < 1e-9);
+        assert!(contact.push.0 > 0.0 && contact.push.1 > 0.0);
+    }
+}
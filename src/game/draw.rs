@@ -1,14 +1,16 @@
 use super::{
     camera::Camera,
-    components::{ColorDrawType, Colorable, Position, Rectangle},
+    components::{ColorDrawType, Colorable, Position, Rectangle, Shape, Side},
+    grapple::Grapple,
+    render::{DrawCommand, DrawCommands, Screen},
 };
 use bevy_ecs::{
     schedule::ScheduleLabel,
-    system::{NonSendMut, Query, ResMut},
+    system::{Query, Res, ResMut},
 };
 use sdl2::{
-    rect::Rect,
-    render::{BlendMode, WindowCanvas},
+    pixels::Color,
+    rect::{Point, Rect},
 };
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, ScheduleLabel)]
@@ -16,22 +18,76 @@ pub struct Render;
 
 pub fn draw(
     mut camera: ResMut<Camera>, // TODO: Usar Res qdo n desenhar mais hitbox
-    query: Query<(&Position, &Rectangle, &Colorable)>,
-    mut canvas: NonSendMut<WindowCanvas>,
+    query: Query<(&Position, &Rectangle, &Colorable, Option<&Shape>)>,
+    screen: Res<Screen>,
+    mut frame: ResMut<DrawCommands>,
 ) {
-    for (pos, rect, colorable) in query.iter() {
+    for (pos, rect, colorable, shape) in query.iter() {
         let square = Rect::new(
             pos.x as i32 - camera.pos.x.floor() as i32,
-            canvas.window().size().1 as i32 - pos.y as i32 - rect.height as i32
-                + camera.pos.y.floor() as i32,
+            screen.height as i32 - pos.y as i32 - rect.height as i32 + camera.pos.y.floor() as i32,
             rect.width,
             rect.height,
         );
-        canvas.set_draw_color(colorable.color);
-        canvas.set_blend_mode(BlendMode::Blend);
-        match colorable.draw_type {
-            ColorDrawType::Fill => canvas.fill_rect(square).expect("Can't fill rect"),
-            ColorDrawType::Outline => canvas.draw_rect(square).expect("Can't draw rect"),
+        let color = colorable.color;
+        let fill = matches!(colorable.draw_type, ColorDrawType::Fill);
+        let command = match shape {
+            None => DrawCommand::Rect {
+                rect: square,
+                color,
+                fill,
+            },
+            Some(Shape::Slope { high }) => {
+                let (left, right) = (square.left() as i16, square.right() as i16);
+                let (top, bottom) = (square.top() as i16, square.bottom() as i16);
+                let peak = match high {
+                    Side::Left => left,
+                    Side::Right => right,
+                };
+                DrawCommand::Polygon {
+                    vx: vec![left, right, peak],
+                    vy: vec![bottom, bottom, top],
+                    color,
+                    fill,
+                }
+            }
+            Some(Shape::Circle) => {
+                let center = square.center();
+                DrawCommand::Circle {
+                    x: center.x() as i16,
+                    y: center.y() as i16,
+                    radius: (rect.width.min(rect.height) / 2) as i16,
+                    color,
+                    fill,
+                }
+            }
         };
+        frame.push(command);
+    }
+}
+
+pub fn draw_ropes(
+    camera: Res<Camera>,
+    query: Query<(&Position, &Rectangle, &Grapple)>,
+    screen: Res<Screen>,
+    mut frame: ResMut<DrawCommands>,
+) {
+    let height = screen.height as i32;
+    let to_screen = |pos: &Position| {
+        Point::new(
+            (pos.x - camera.pos.x.floor()) as i32,
+            height - (pos.y - camera.pos.y.floor()) as i32,
+        )
+    };
+    for (pos, rect, grapple) in query.iter() {
+        let Some(anchor) = grapple.anchor() else {
+            continue;
+        };
+        let center = rect.on_position(pos).center();
+        frame.push(DrawCommand::Line {
+            start: to_screen(&center),
+            end: to_screen(&anchor.point),
+            color: Color::WHITE,
+        });
     }
 }
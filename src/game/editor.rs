@@ -0,0 +1,710 @@
+use super::{
+    camera::Camera,
+    components::{Player, Position, Rectangle, Velocity},
+    history::{Change, History},
+    input::MouseCommand,
+    map::{ColorName, Entity, MapSource},
+    render::{DrawCommand, DrawCommands, Screen},
+    resources::Spawn,
+    startup::{CurrentMap, MapSources},
+};
+use bevy_ecs::{
+    change_detection::DetectChanges,
+    entity::Entity as EcsEntity,
+    event::EventReader,
+    query::Has,
+    schedule::ScheduleLabel,
+    system::{Commands, Query, Res, ResMut, Resource, SystemParam},
+};
+use log::{error, info};
+use sdl2::{
+    keyboard::{Keycode, Mod},
+    pixels::Color,
+    rect::Rect,
+};
+use serde_json::{json, Value};
+
+/// Roda todo frame, antes do [Update](super::Update). Enquanto o editor está aberto o
+/// [Update](super::Update) não roda, então o jogo fica pausado
+#[derive(Debug, Clone, Eq, PartialEq, Hash, ScheduleLabel)]
+pub struct EditorUpdate;
+
+pub const DEFAULT_GRID: f64 = 10.0;
+const MAX_GRID: f64 = 160.0;
+/// Quanto as setas movem a câmera quando nada está selecionado
+const PAN_STEP: f64 = 100.0;
+
+/// Tecla apertada. Com o editor aberto as teclas não chegam no [InputState](super::input::InputState)
+#[derive(Debug, Clone, Copy, bevy_ecs::event::Event)]
+pub struct EditorKey {
+    pub key: Keycode,
+    pub keymod: Mod,
+    pub repeat: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Tool {
+    /// Clicar seleciona, arrastar a seleção move
+    #[default]
+    Select,
+    /// Arrastar desenha um estático
+    Static,
+    Coin,
+    /// Arrastar desenha uma kill zone
+    KillZone,
+    /// Clicar muda o ponto de spawn do player
+    Spawn,
+}
+
+impl Tool {
+    fn from_key(key: Keycode) -> Option<Self> {
+        let tool = match key {
+            Keycode::Num1 => Tool::Select,
+            Keycode::Num2 => Tool::Static,
+            Keycode::Num3 => Tool::Coin,
+            Keycode::Num4 => Tool::KillZone,
+            Keycode::Num5 => Tool::Spawn,
+            _ => return None,
+        };
+        Some(tool)
+    }
+}
+
+/// Editor de fases, aberto e fechado com F1
+///
+/// Ferramentas nas teclas 1 a 5 ([Tool]). Com algo selecionado as setas movem, Shift + setas
+/// redimensionam, C troca a cor e Delete apaga. `[` e `]` mudam o grid e Ctrl+S salva o mapa.
+/// Ctrl+Z desfaz e Ctrl+Y ou Ctrl+Shift+Z refazem
+#[derive(Debug, Resource)]
+pub struct Editor {
+    pub active: bool,
+    pub tool: Tool,
+    /// Tamanho do grid em que tudo que o editor cria ou move é alinhado
+    pub grid: f64,
+    selected: Option<EcsEntity>,
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Self::with_grid(DEFAULT_GRID)
+    }
+}
+
+impl Editor {
+    pub fn with_grid(grid: f64) -> Self {
+        Self {
+            active: false,
+            tool: Tool::Select,
+            grid: grid.clamp(1.0, MAX_GRID),
+            selected: None,
+        }
+    }
+
+    fn snap(&self, position: &Position) -> Position {
+        Position::new(snap(position.x, self.grid), snap(position.y, self.grid))
+    }
+}
+
+fn snap(value: f64, grid: f64) -> f64 {
+    (value / grid).round() * grid
+}
+
+/// Números inteiros ficam sem o `.0`, como nos mapas escritos à mão
+fn number(value: f64) -> Value {
+    if value.fract() == 0.0 {
+        json!(value as i64)
+    } else {
+        json!(value)
+    }
+}
+
+fn position_json(position: &Position) -> Value {
+    json!({ "x": number(position.x), "y": number(position.y) })
+}
+
+fn rectangle_json(width: f64, height: f64) -> Value {
+    json!({ "width": number(width), "height": number(height) })
+}
+
+fn kind(source: &Value) -> &str {
+    source["entity"].as_str().unwrap_or_default()
+}
+
+fn source_position(source: &Value) -> Option<Position> {
+    serde_json::from_value(source.get("position")?.clone()).ok()
+}
+
+fn with_position(source: &Value, position: &Position) -> Option<Value> {
+    // Áreas infinitas não têm posição
+    source.get("position")?;
+    let mut source = source.clone();
+    source["position"] = position_json(position);
+    Some(source)
+}
+
+fn translate(source: &Value, dx: f64, dy: f64) -> Option<Value> {
+    let position = source_position(source)?;
+    with_position(source, &Position::new(position.x + dx, position.y + dy))
+}
+
+/// Muda o tamanho sem deixar ficar menor que `min`. O player e as moedas têm tamanho fixo
+fn resize(source: &Value, current: Rectangle, dw: f64, dh: f64, min: f64) -> Option<Value> {
+    if matches!(kind(source), "player" | "coin") {
+        return None;
+    }
+    let rectangle = source
+        .get("rectangle")
+        .and_then(|r| serde_json::from_value::<Rectangle>(r.clone()).ok())
+        .unwrap_or(current);
+    let mut source = source.clone();
+    source["rectangle"] = rectangle_json(
+        (rectangle.width as f64 + dw).max(min),
+        (rectangle.height as f64 + dh).max(min),
+    );
+    Some(source)
+}
+
+/// Passa para a próxima cor da [ColorName::PALETTE]
+fn recolor(source: &Value) -> Option<Value> {
+    if matches!(kind(source), "player" | "kill_zone") {
+        return None;
+    }
+    let palette = ColorName::PALETTE;
+    let next = source["color"]
+        .as_str()
+        .and_then(|color| palette.iter().position(|name| *name == color))
+        .map_or(0, |i| (i + 1) % palette.len());
+    let mut source = source.clone();
+    source["color"] = json!(palette[next]);
+    Some(source)
+}
+
+/// Retângulo entre dois cantos, `None` se não tiver área
+fn rect_between(a: &Position, b: &Position) -> Option<(Position, f64, f64)> {
+    let (width, height) = ((a.x - b.x).abs(), (a.y - b.y).abs());
+    if width == 0.0 || height == 0.0 {
+        return None;
+    }
+    Some((Position::new(a.x.min(b.x), a.y.min(b.y)), width, height))
+}
+
+type MapEntities<'w, 's> = Query<
+    'w,
+    's,
+    (
+        EcsEntity,
+        &'static MapSource,
+        Option<&'static mut Position>,
+        Option<&'static Rectangle>,
+        Option<&'static mut Velocity>,
+        Has<Player>,
+    ),
+>;
+
+/// Alterações nas entidades do mapa. Toda alteração passa pelo [MapSource] e pelos
+/// [MapSources], que é o que é salvo, e fica no [History] para poder ser desfeita
+#[derive(SystemParam)]
+pub struct MapEdits<'w, 's> {
+    commands: Commands<'w, 's>,
+    entities: MapEntities<'w, 's>,
+    sources: ResMut<'w, MapSources>,
+    spawn: ResMut<'w, Spawn>,
+    history: ResMut<'w, History>,
+}
+
+impl MapEdits<'_, '_> {
+    pub fn source(&self, entity: EcsEntity) -> Option<&Value> {
+        self.entities
+            .get(entity)
+            .ok()
+            .map(|(_, source, ..)| &source.0)
+    }
+
+    fn rectangle(&self, entity: EcsEntity) -> Option<Rectangle> {
+        self.entities
+            .get(entity)
+            .ok()
+            .and_then(|(.., r, _, _)| r.copied())
+    }
+
+    pub fn player(&self) -> Option<EcsEntity> {
+        self.entities
+            .iter()
+            .find(|(.., is_player)| *is_player)
+            .map(|(entity, ..)| entity)
+    }
+
+    /// A menor entidade que contém `point`, assim dá para selecionar o que está em cima de um
+    /// estático grande
+    pub fn entity_at(&self, point: &Position) -> Option<EcsEntity> {
+        self.entities
+            .iter()
+            .filter_map(|(entity, _, position, rectangle, ..)| {
+                let hitbox = rectangle?.on_position(position?);
+                let inside = (hitbox.left()..=hitbox.right()).contains(&point.x)
+                    && (hitbox.bottom()..=hitbox.top()).contains(&point.y);
+                inside.then_some((entity, rectangle?.width * rectangle?.height))
+            })
+            .min_by_key(|(_, area)| *area)
+            .map(|(entity, _)| entity)
+    }
+
+    /// Cria uma entidade a partir do JSON, no mesmo formato do arquivo do mapa
+    pub fn place(&mut self, source: Value) -> Option<EcsEntity> {
+        self.commit(Change {
+            entity: EcsEntity::PLACEHOLDER,
+            before: None,
+            after: Some(source),
+        })
+    }
+
+    /// Troca a entidade por uma nova criada de `source` e retorna a nova. O player não é recriado,
+    /// só o spawn dele muda
+    pub fn replace(&mut self, entity: EcsEntity, source: Value) -> Option<EcsEntity> {
+        let before = self.source(entity)?.clone();
+        self.commit(Change {
+            entity,
+            before: Some(before),
+            after: Some(source),
+        })
+    }
+
+    /// Apaga a entidade, menos o player que o mapa precisa ter
+    pub fn remove(&mut self, entity: EcsEntity) -> bool {
+        let Ok((_, source, .., false)) = self.entities.get(entity) else {
+            return false;
+        };
+        let before = source.0.clone();
+        self.commit(Change {
+            entity,
+            before: Some(before),
+            after: None,
+        });
+        true
+    }
+
+    /// Desfaz a última alteração e retorna a entidade afetada, se ela ainda existir
+    pub fn undo(&mut self) -> Option<EcsEntity> {
+        let change = self.next_change(History::undo)?;
+        let change = self.reapply(change);
+        let entity = change.after.is_some().then_some(change.entity);
+        self.history.undone(change);
+        entity
+    }
+
+    /// Refaz a última alteração desfeita e retorna a entidade afetada, se ela ainda existir
+    pub fn redo(&mut self) -> Option<EcsEntity> {
+        let change = self.next_change(History::redo)?;
+        let change = self.reapply(change);
+        let entity = change.after.is_some().then_some(change.entity);
+        self.history.redone(change);
+        entity
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Tira alterações do histórico até achar uma que ainda dá para aplicar. As de entidades que
+    /// o jogo removeu, como uma moeda pega depois de mexida, são descartadas
+    fn next_change(&mut self, take: fn(&mut History) -> Option<Change>) -> Option<Change> {
+        loop {
+            let change = take(&mut self.history)?;
+            if change.before.is_none() || self.commands.get_entity(change.entity).is_some() {
+                return Some(change);
+            }
+        }
+    }
+
+    /// Aplica uma alteração que já estava no histórico. Se a entidade for recriada, o histórico
+    /// passa a apontar para a nova
+    fn reapply(&mut self, mut change: Change) -> Change {
+        if let Some(entity) = self.apply(&change) {
+            if entity != change.entity {
+                self.history.remap(change.entity, entity);
+                change.entity = entity;
+            }
+        }
+        change
+    }
+
+    fn commit(&mut self, mut change: Change) -> Option<EcsEntity> {
+        let entity = self.apply(&change);
+        if change.after.is_some() && entity.is_none() {
+            return None;
+        }
+        if let Some(entity) = entity {
+            self.history.remap(change.entity, entity);
+            change.entity = entity;
+        }
+        self.history.record(change);
+        entity
+    }
+
+    /// Leva a entidade do estado `before` para o `after` da alteração
+    fn apply(&mut self, change: &Change) -> Option<EcsEntity> {
+        let Some(source) = change.after.clone() else {
+            self.sources.0.remove(&change.entity);
+            if let Some(mut entity) = self.commands.get_entity(change.entity) {
+                entity.despawn();
+            }
+            return None;
+        };
+        if kind(&source) == "player" {
+            return self.move_player(change.entity, source);
+        }
+
+        let entity = serde_json::from_value::<Entity>(source.clone())
+            .map_err(|err| error!("Invalid map entity {source}: {err}"))
+            .ok()?;
+        let id = entity.spawn(&mut self.commands);
+        self.sources.0.insert(id, source.clone());
+        self.commands.entity(id).insert(MapSource(source));
+        if change.before.is_some() {
+            self.sources.0.remove(&change.entity);
+            if let Some(mut entity) = self.commands.get_entity(change.entity) {
+                entity.despawn();
+            }
+        }
+        Some(id)
+    }
+
+    fn move_player(&mut self, entity: EcsEntity, source: Value) -> Option<EcsEntity> {
+        let spawn = source_position(&source)?;
+        let (_, _, position, _, velocity, _) = self.entities.get_mut(entity).ok()?;
+        if let Some(mut position) = position {
+            *position = spawn.clone();
+        }
+        if let Some(mut velocity) = velocity {
+            *velocity = Velocity::default();
+        }
+        self.spawn.0 = spawn;
+        self.sources.0.insert(entity, source.clone());
+        self.commands.entity(entity).insert(MapSource(source));
+        Some(entity)
+    }
+
+    fn edit(
+        &mut self,
+        entity: EcsEntity,
+        change: impl FnOnce(&Value) -> Option<Value>,
+    ) -> Option<EcsEntity> {
+        let source = change(self.source(entity)?)?;
+        self.replace(entity, source)
+    }
+
+    /// O mapa no formato que o [init_map_system](super::startup::init_map_system) carrega, com
+    /// o player primeiro. Inclui o que o jogo já removeu, ver [MapSources]
+    fn map_sources(&self) -> Vec<&Value> {
+        let mut sources: Vec<_> = self.sources.0.values().collect();
+        sources.sort_by_key(|source| kind(source) != "player");
+        sources
+    }
+
+    pub fn save(&self, path: &str) {
+        let result = serde_json::to_string_pretty(&self.map_sources())
+            .map_err(|err| err.to_string())
+            .and_then(|map| std::fs::write(path, map + "\n").map_err(|err| err.to_string()));
+        match result {
+            Ok(()) => info!("Map saved to {path}"),
+            Err(err) => error!("Failed to save map to {path}: {err}"),
+        }
+    }
+}
+
+pub fn handle_editor_keys(
+    mut keys: EventReader<EditorKey>,
+    mut editor: ResMut<Editor>,
+    mut camera: ResMut<Camera>,
+    current_map: Res<CurrentMap>,
+    mut edits: MapEdits,
+) {
+    // As entidades do histórico não existem mais depois de trocar de mapa
+    if current_map.is_changed() {
+        edits.clear_history();
+        editor.selected = None;
+    }
+    for &EditorKey {
+        key,
+        keymod,
+        repeat,
+    } in keys.read()
+    {
+        if key == Keycode::F1 {
+            if !repeat {
+                editor.active = !editor.active;
+                info!("Editor {}", if editor.active { "opened" } else { "closed" });
+            }
+            continue;
+        }
+        if !editor.active {
+            continue;
+        }
+        if let Some(tool) = Tool::from_key(key) {
+            editor.tool = tool;
+            continue;
+        }
+
+        let ctrl = keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD);
+        let shift = keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD);
+        let grid = editor.grid;
+        let direction = match key {
+            Keycode::Left => Some((-1.0, 0.0)),
+            Keycode::Right => Some((1.0, 0.0)),
+            Keycode::Up => Some((0.0, 1.0)),
+            Keycode::Down => Some((0.0, -1.0)),
+            _ => None,
+        };
+        match (key, editor.selected, direction) {
+            (Keycode::Z, _, _) if ctrl && shift => editor.selected = edits.redo(),
+            (Keycode::Z, _, _) if ctrl => editor.selected = edits.undo(),
+            (Keycode::Y, _, _) if ctrl => editor.selected = edits.redo(),
+            (Keycode::S, _, _) if ctrl => edits.save(&current_map.0),
+            (Keycode::LeftBracket, _, _) => editor.grid = (grid / 2.0).max(1.0),
+            (Keycode::RightBracket, _, _) => editor.grid = (grid * 2.0).min(MAX_GRID),
+            (Keycode::Delete | Keycode::Backspace, Some(selected), _) => {
+                editor.selected = (!edits.remove(selected)).then_some(selected);
+            }
+            (Keycode::C, Some(selected), _) => {
+                if let Some(entity) = edits.edit(selected, recolor) {
+                    editor.selected = Some(entity);
+                }
+            }
+            (_, Some(selected), Some((x, y))) => {
+                let edited = if shift {
+                    let current = edits.rectangle(selected);
+                    edits.edit(selected, |source| {
+                        resize(source, current?, x * grid, y * grid, grid)
+                    })
+                } else {
+                    edits.edit(selected, |source| translate(source, x * grid, y * grid))
+                };
+                if let Some(entity) = edited {
+                    editor.selected = Some(entity);
+                }
+            }
+            (_, None, Some((x, y))) => {
+                camera.pos.x += x * PAN_STEP;
+                camera.pos.y += y * PAN_STEP;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Aplica a ferramenta atual nos cliques e arrastos do mouse
+pub fn apply_editor_tool(
+    mut mouse_commands: EventReader<MouseCommand>,
+    mut editor: ResMut<Editor>,
+    mut edits: MapEdits,
+) {
+    for MouseCommand { press, lift } in mouse_commands.read() {
+        if !editor.active {
+            continue;
+        }
+        let (start, end) = (editor.snap(&press.0), editor.snap(&lift.0));
+        match editor.tool {
+            Tool::Select => {
+                let dragging_selection = start != end
+                    && editor
+                        .selected
+                        .is_some_and(|selected| edits.entity_at(&press.0) == Some(selected));
+                editor.selected = match editor.selected {
+                    Some(selected) if dragging_selection => edits
+                        .edit(selected, |source| {
+                            translate(source, end.x - start.x, end.y - start.y)
+                        })
+                        .or(Some(selected)),
+                    _ => edits.entity_at(&lift.0),
+                };
+            }
+            Tool::Static | Tool::KillZone => {
+                let Some((position, width, height)) = rect_between(&start, &end) else {
+                    continue;
+                };
+                let mut source = json!({
+                    "entity": "static",
+                    "position": position_json(&position),
+                    "rectangle": rectangle_json(width, height),
+                    "color": "cyan",
+                });
+                if editor.tool == Tool::KillZone {
+                    source = json!({
+                        "entity": "kill_zone",
+                        "position": source["position"],
+                        "rectangle": source["rectangle"],
+                    });
+                }
+                editor.selected = edits.place(source);
+            }
+            Tool::Coin => {
+                editor.selected = edits.place(json!({
+                    "entity": "coin",
+                    "position": position_json(&end),
+                    "color": "yellow",
+                }));
+            }
+            Tool::Spawn => {
+                if let Some(player) = edits.player() {
+                    edits.edit(player, |source| with_position(source, &end));
+                }
+            }
+        }
+    }
+}
+
+/// Contorna a seleção e mostra a ferramenta e o grid no canto da tela
+pub fn draw_editor(
+    editor: Res<Editor>,
+    camera: Res<Camera>,
+    query: Query<(&Position, &Rectangle)>,
+    screen: Res<Screen>,
+    mut frame: ResMut<DrawCommands>,
+) {
+    if !editor.active {
+        return;
+    }
+    let height = screen.height as i32;
+    if let Some((pos, rect)) = editor.selected.and_then(|entity| query.get(entity).ok()) {
+        let outline = Rect::new(
+            pos.x as i32 - camera.pos.x.floor() as i32 - 2,
+            height - pos.y as i32 - rect.height as i32 + camera.pos.y.floor() as i32 - 2,
+            rect.width + 4,
+            rect.height + 4,
+        );
+        frame.push(DrawCommand::Rect {
+            rect: outline,
+            color: Color::YELLOW,
+            fill: false,
+        });
+    }
+    frame.push(DrawCommand::Text {
+        x: 10,
+        y: 10,
+        text: format!("EDITOR  tool: {:?}  grid: {}", editor.tool, editor.grid),
+        color: Color::WHITE,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::startup::init_map_system;
+    use bevy_ecs::{schedule::Schedule, world::World};
+
+    #[test]
+    fn snaps_to_grid() {
+        assert_eq!(snap(14.0, 10.0), 10.0);
+        assert_eq!(snap(15.0, 10.0), 20.0);
+        assert_eq!(snap(-14.0, 10.0), -10.0);
+        assert_eq!(number(20.0), json!(20));
+        assert_eq!(number(2.5), json!(2.5));
+    }
+
+    #[test]
+    fn edits_map_source() {
+        let source = json!({
+            "entity": "static",
+            "position": { "x": 100, "y": 100 },
+            "rectangle": { "width": 400, "height": 10 },
+            "color": "green",
+        });
+
+        let moved = translate(&source, 10.0, -20.0).unwrap();
+        assert_eq!(moved["position"], json!({ "x": 110, "y": 80 }));
+
+        let current = Rectangle::new(400, 10);
+        let resized = resize(&source, current, -10.0, -10.0, 10.0).unwrap();
+        assert_eq!(resized["rectangle"], json!({ "width": 390, "height": 10 }));
+
+        assert_eq!(recolor(&source).unwrap()["color"], json!("blue"));
+        assert_eq!(
+            recolor(&json!({ "entity": "crate" })).unwrap()["color"],
+            json!("white")
+        );
+        assert!(recolor(&json!({ "entity": "player" })).is_none());
+
+        // Kill zone infinita não tem posição para mover
+        let infinite = json!({ "entity": "kill_zone", "start": -500, "direction": "down" });
+        assert!(translate(&infinite, 10.0, 0.0).is_none());
+    }
+
+    /// Mundo com o primeiro mapa carregado e tudo que o [MapEdits] precisa
+    fn map_world() -> World {
+        let mut world = World::new();
+        world.insert_resource(Spawn::new());
+        world.insert_resource(CurrentMap::default());
+        world.init_resource::<MapSources>();
+        world.init_resource::<History>();
+        Schedule::default()
+            .add_systems(init_map_system)
+            .run(&mut world);
+        world
+    }
+
+    fn find(world: &World, predicate: impl Fn(&Value) -> bool) -> EcsEntity {
+        world
+            .resource::<MapSources>()
+            .0
+            .iter()
+            .find(|(_, source)| predicate(source))
+            .map(|(entity, _)| *entity)
+            .unwrap()
+    }
+
+    #[test]
+    fn saves_entities_removed_by_the_game() {
+        let mut world = map_world();
+        let total = world.resource::<MapSources>().0.len();
+        // Moeda pega pelo player
+        let coin = find(&world, |s| kind(s) == "coin");
+        world.despawn(coin);
+        let static_entity = find(&world, |s| kind(s) == "static");
+
+        Schedule::default()
+            .add_systems(move |mut edits: MapEdits| {
+                assert!(edits.remove(static_entity));
+            })
+            .run(&mut world);
+
+        Schedule::default()
+            .add_systems(move |edits: MapEdits| {
+                let sources = edits.map_sources();
+                assert_eq!(sources.len(), total - 1);
+                assert_eq!(kind(sources[0]), "player");
+                assert_eq!(sources.iter().filter(|s| kind(s) == "coin").count(), 4);
+            })
+            .run(&mut world);
+        assert!(world.get_entity(static_entity).is_err());
+    }
+
+    #[test]
+    fn undo_skips_entities_removed_by_the_game() {
+        let mut world = map_world();
+        let static_entity = find(&world, |s| kind(s) == "static");
+        let coin = find(&world, |s| kind(s) == "coin");
+        let sources = &world.resource::<MapSources>().0;
+        let original = sources[&static_entity].clone();
+        let moved_coin = translate(&sources[&coin], 10.0, 0.0).unwrap();
+
+        Schedule::default()
+            .add_systems(move |mut edits: MapEdits| {
+                edits.edit(static_entity, |s| translate(s, 10.0, 0.0));
+                edits.edit(coin, |s| translate(s, 10.0, 0.0));
+            })
+            .run(&mut world);
+
+        // A moeda mexida é pega antes de desfazer
+        let moved_coin = find(&world, |s| *s == moved_coin);
+        world.despawn(moved_coin);
+
+        Schedule::default()
+            .add_systems(|mut edits: MapEdits| {
+                assert!(edits.undo().is_some());
+                assert_eq!(edits.undo(), None);
+            })
+            .run(&mut world);
+        let restored = find(&world, |s| *s == original);
+        assert!(world.get_entity(restored).is_ok());
+    }
+}
@@ -0,0 +1,206 @@
+use super::{
+    components::{CollisionLayers, Health, Layers, Normal, Player, Position, Rectangle, Velocity},
+    physics::Impulse,
+};
+use bevy_ecs::{
+    entity::Entity,
+    event::EventReader,
+    prelude::Component,
+    query::Has,
+    system::{Commands, Query},
+};
+use log::debug;
+use serde::Deserialize;
+
+/// Quanto tempo o empurrão de uma explosão pode passar da velocidade máxima de quem foi atingido
+const KNOCKBACK_MILLIS: u64 = 250;
+
+/// Como a força da explosão diminui do centro até a borda do raio
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Falloff {
+    Constant,
+    #[default]
+    Linear,
+    Quadratic,
+}
+
+impl Falloff {
+    /// Fração da força a `distance` do centro, de 1 no centro até 0 no raio
+    pub fn factor(&self, distance: f64, radius: f64) -> f64 {
+        if distance >= radius {
+            return 0.0;
+        }
+        let remaining = 1.0 - distance / radius;
+        match self {
+            Falloff::Constant => 1.0,
+            Falloff::Linear => remaining,
+            Falloff::Quadratic => remaining.powi(2),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Blast {
+    pub radius: f64,
+    /// Velocidade somada no centro da explosão, em pixels por segundo
+    pub strength: f64,
+    pub falloff: Falloff,
+    /// Dano no centro da explosão, diminui com o mesmo [Falloff]
+    pub damage: u32,
+    /// Entidades nessas camadas que estiverem no raio são destruídas
+    pub destroys: Layers,
+}
+
+impl Default for Blast {
+    fn default() -> Self {
+        Self {
+            radius: 200.0,
+            strength: 2500.0,
+            falloff: Falloff::Linear,
+            damage: 0,
+            destroys: Layers::NONE,
+        }
+    }
+}
+
+/// Entidades que explodem ao serem detonadas
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Explosive(pub Blast);
+
+#[derive(Debug, Clone, bevy_ecs::event::Event)]
+pub struct Explosion {
+    pub center: Position,
+    pub blast: Blast,
+    /// Quem explodiu, que não é afetado pela própria explosão
+    pub source: Option<Entity>,
+}
+
+/// O que uma explosão pode empurrar ou ferir
+type BlastTargets<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Position,
+        &'static Rectangle,
+        Option<&'static mut Velocity>,
+        Option<&'static mut Health>,
+        Option<&'static CollisionLayers>,
+        Has<Player>,
+    ),
+>;
+
+/// Empurra todas as entidades com [Velocity] no raio das [Explosion]s, para longe do centro
+pub fn apply_explosions(
+    mut explosions: EventReader<Explosion>,
+    mut targets: BlastTargets,
+    mut commands: Commands,
+) {
+    for explosion in explosions.read() {
+        let Explosion {
+            center,
+            blast,
+            source,
+        } = explosion;
+        for (entity, position, rectangle, velocity, health, layers, is_player) in targets.iter_mut()
+        {
+            if Some(entity) == *source {
+                continue;
+            }
+            let hitbox = rectangle.on_position(position);
+            // Distância até o ponto mais perto da hitbox, assim entidades grandes também são
+            // atingidas pela borda
+            let closest_x = center.x.clamp(hitbox.left(), hitbox.right());
+            let closest_y = center.y.clamp(hitbox.bottom(), hitbox.top());
+            let distance = ((closest_x - center.x).powi(2) + (closest_y - center.y).powi(2)).sqrt();
+            let factor = blast.falloff.factor(distance, blast.radius);
+            if factor <= 0.0 {
+                continue;
+            }
+
+            let membership = layers.map_or(Layers::ALL, |l| l.membership);
+            if !is_player && membership.intersects(blast.destroys) {
+                commands.entity(entity).despawn();
+                continue;
+            }
+
+            if let Some(mut velocity) = velocity {
+                let target = hitbox.center();
+                let away = Normal::new(target.x - center.x, target.y - center.y);
+                // No centro exato da explosão não tem direção, então joga para cima
+                let away = if away.x() == 0.0 && away.y() == 0.0 {
+                    Normal::new(0.0, 1.0)
+                } else {
+                    away
+                };
+                velocity.x += away.x() * blast.strength * factor;
+                velocity.y += away.y() * blast.strength * factor;
+                commands
+                    .entity(entity)
+                    .try_insert(Impulse::from_millis(KNOCKBACK_MILLIS));
+            }
+
+            let damage = (blast.damage as f64 * factor).round() as u32;
+            if let Some(mut health) = health.filter(|_| damage > 0) {
+                if health.damage(damage) && !is_player {
+                    debug!("{:?} destroyed by explosion", entity);
+                    commands.entity(entity).despawn();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{components::PhysicsBody, physics::limit_velocity, resources::Time};
+    use bevy_ecs::{
+        event::EventRegistry,
+        schedule::{IntoSystemConfigs, Schedule},
+        world::World,
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn falloff_reaches_zero_at_radius() {
+        for falloff in [Falloff::Constant, Falloff::Linear, Falloff::Quadratic] {
+            assert_eq!(falloff.factor(0.0, 100.0), 1.0);
+            assert_eq!(falloff.factor(100.0, 100.0), 0.0);
+        }
+        assert_eq!(Falloff::Linear.factor(50.0, 100.0), 0.5);
+        assert_eq!(Falloff::Quadratic.factor(50.0, 100.0), 0.25);
+    }
+
+    #[test]
+    fn knockback_exceeds_max_speed() {
+        let mut world = World::new();
+        world.insert_resource(Time::fixed(Duration::from_millis(16)));
+        EventRegistry::register_event::<Explosion>(&mut world);
+        let body = PhysicsBody::PLAYER;
+        let target = world
+            .spawn((
+                Position::new(10, 0),
+                Rectangle::new(10, 10),
+                Velocity::default(),
+                body,
+            ))
+            .id();
+        world.send_event(Explosion {
+            center: Position::new(0, 5),
+            blast: Blast {
+                strength: body.max_horizontal_speed * 2.0,
+                ..Default::default()
+            },
+            source: None,
+        });
+
+        Schedule::default()
+            .add_systems((apply_explosions, limit_velocity).chain())
+            .run(&mut world);
+        let velocity = world.get::<Velocity>(target).unwrap();
+        assert!(velocity.x > body.max_horizontal_speed);
+    }
+}
@@ -0,0 +1,286 @@
+use super::input::{Action, ActionState, InputEvent, InputSource};
+use log::{error, info};
+use sdl2::{
+    controller::{Axis, Button, GameController},
+    event::Event,
+    GameControllerSubsystem,
+};
+use std::collections::{HashMap, HashSet};
+
+/// Fração do curso do analógico que é ignorada, controles parados nunca ficam exatamente no zero
+pub const STICK_DEADZONE: f64 = 0.25;
+
+fn button_action(button: Button) -> Option<Action> {
+    let action = match button {
+        Button::A => Action::Jump,
+        Button::B => Action::Dash,
+        Button::X => Action::Attack,
+        Button::Y => Action::Grapple,
+        Button::LeftShoulder => Action::Pound,
+        Button::RightShoulder => Action::Detonate,
+        Button::DPadUp => Action::Up,
+        Button::DPadDown => Action::Down,
+        Button::DPadLeft => Action::Left,
+        Button::DPadRight => Action::Right,
+        _ => return None,
+    };
+    Some(action)
+}
+
+/// Converte o valor de um eixo do SDL na força para o lado negativo e para o positivo, de 0 a 1
+///
+/// A força começa a contar a partir da zona morta, então o analógico vai de 0 a 1 suavemente
+fn stick_strength(value: i16, deadzone: f64) -> (f64, f64) {
+    let value = (value as f64 / i16::MAX as f64).clamp(-1.0, 1.0);
+    let strength = ((value.abs() - deadzone) / (1.0 - deadzone)).max(0.0);
+    if value < 0.0 {
+        (strength, 0.0)
+    } else {
+        (0.0, strength)
+    }
+}
+
+/// Traduz os eventos dos controles em [InputEvent]s, lembrando o que cada controle está apertando
+#[derive(Debug)]
+pub struct GamepadInputs {
+    /// Ações apertadas pelos botões de cada controle
+    buttons: HashMap<u32, HashSet<Action>>,
+    /// Última força de cada lado do analógico esquerdo, para só mandar evento quando ela muda
+    sticks: HashMap<(u32, Axis), (f64, f64)>,
+    pub deadzone: f64,
+}
+
+impl Default for GamepadInputs {
+    fn default() -> Self {
+        Self {
+            buttons: HashMap::new(),
+            sticks: HashMap::new(),
+            deadzone: STICK_DEADZONE,
+        }
+    }
+}
+
+impl GamepadInputs {
+    pub fn input_events(&mut self, event: &Event) -> Vec<InputEvent> {
+        match *event {
+            Event::ControllerDeviceRemoved { which, .. } => self.release(which),
+            Event::ControllerButtonDown { which, button, .. } => button_action(button)
+                .filter(|action| self.buttons.entry(which).or_default().insert(*action))
+                .map(|action| {
+                    InputEvent::new(action, ActionState::Active)
+                        .with_source(InputSource::Button(which))
+                })
+                .into_iter()
+                .collect(),
+            Event::ControllerButtonUp { which, button, .. } => button_action(button)
+                .filter(|action| {
+                    self.buttons
+                        .get_mut(&which)
+                        .is_some_and(|held| held.remove(action))
+                })
+                .map(|action| {
+                    InputEvent::new(action, ActionState::Inactive)
+                        .with_source(InputSource::Button(which))
+                })
+                .into_iter()
+                .collect(),
+            Event::ControllerAxisMotion {
+                which, axis, value, ..
+            } => {
+                let Some((negative, positive)) = stick_actions(axis) else {
+                    return Vec::new();
+                };
+                let strength = stick_strength(value, self.deadzone);
+                let previous = self.sticks.insert((which, axis), strength);
+                if previous == Some(strength) || (previous.is_none() && strength == (0.0, 0.0)) {
+                    return Vec::new();
+                }
+                let source = InputSource::Stick(which);
+                vec![
+                    InputEvent::analog(negative, strength.0).with_source(source),
+                    InputEvent::analog(positive, strength.1).with_source(source),
+                ]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Solta só o que o controle `which` estava apertando, senão fica preso depois que ele sai
+    fn release(&mut self, which: u32) -> Vec<InputEvent> {
+        let buttons = self
+            .buttons
+            .remove(&which)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|action| {
+                InputEvent::new(action, ActionState::Inactive)
+                    .with_source(InputSource::Button(which))
+            });
+        let mut sticks = Vec::new();
+        self.sticks.retain(|&(id, axis), strength| {
+            if id != which {
+                return true;
+            }
+            if let Some((negative, positive)) = stick_actions(axis) {
+                for (action, strength) in [(negative, strength.0), (positive, strength.1)] {
+                    if strength > 0.0 {
+                        sticks.push(
+                            InputEvent::analog(action, 0.0).with_source(InputSource::Stick(which)),
+                        );
+                    }
+                }
+            }
+            false
+        });
+        buttons.chain(sticks).collect()
+    }
+}
+
+/// Ações dos lados negativo e positivo de um eixo. No SDL o y do analógico cresce para baixo
+fn stick_actions(axis: Axis) -> Option<(Action, Action)> {
+    match axis {
+        Axis::LeftX => Some((Action::Left, Action::Right)),
+        Axis::LeftY => Some((Action::Up, Action::Down)),
+        _ => None,
+    }
+}
+
+/// Controles conectados, abertos e fechados conforme são plugados
+pub struct Gamepads {
+    subsystem: GameControllerSubsystem,
+    controllers: HashMap<u32, GameController>,
+    inputs: GamepadInputs,
+}
+
+impl Gamepads {
+    pub fn new(subsystem: GameControllerSubsystem) -> Self {
+        Self {
+            subsystem,
+            controllers: HashMap::new(),
+            inputs: GamepadInputs::default(),
+        }
+    }
+
+    /// Converte um evento de controle em [InputEvent]s. Os controles que já estão conectados
+    /// quando o jogo abre também chegam como `ControllerDeviceAdded`
+    pub fn input_events(&mut self, event: &Event) -> Vec<InputEvent> {
+        match *event {
+            Event::ControllerDeviceAdded { which, .. } => {
+                match self.subsystem.open(which) {
+                    Ok(controller) => {
+                        info!("Controller connected: {}", controller.name());
+                        self.controllers
+                            .insert(controller.instance_id(), controller);
+                    }
+                    Err(err) => error!("Failed to open controller {which}: {err}"),
+                }
+                Vec::new()
+            }
+            Event::ControllerDeviceRemoved { which, .. } => {
+                let Some(controller) = self.controllers.remove(&which) else {
+                    return Vec::new();
+                };
+                info!("Controller disconnected: {}", controller.name());
+                self.inputs.input_events(event)
+            }
+            _ => self.inputs.input_events(event),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stick_ignores_deadzone() {
+        assert_eq!(stick_strength(0, 0.25), (0.0, 0.0));
+        assert_eq!(stick_strength(i16::MAX / 5, 0.25), (0.0, 0.0));
+        assert_eq!(stick_strength(i16::MAX, 0.25), (0.0, 1.0));
+        assert_eq!(stick_strength(i16::MIN, 0.25), (1.0, 0.0));
+
+        let (left, right) = stick_strength(-i16::MAX / 2, 0.25);
+        assert!((left - 1.0 / 3.0).abs() < 1e-3);
+        assert_eq!(right, 0.0);
+    }
+
+    fn button(which: u32, button: Button, down: bool) -> Event {
+        if down {
+            Event::ControllerButtonDown {
+                timestamp: 0,
+                which,
+                button,
+            }
+        } else {
+            Event::ControllerButtonUp {
+                timestamp: 0,
+                which,
+                button,
+            }
+        }
+    }
+
+    fn axis(which: u32, axis: Axis, value: i16) -> Event {
+        Event::ControllerAxisMotion {
+            timestamp: 0,
+            which,
+            axis,
+            value,
+        }
+    }
+
+    #[test]
+    fn removal_releases_only_that_controller() {
+        let mut inputs = GamepadInputs::default();
+        let pressed = inputs.input_events(&button(0, Button::A, true));
+        assert_eq!(
+            pressed,
+            [InputEvent::new(Action::Jump, ActionState::Active)
+                .with_source(InputSource::Button(0))]
+        );
+        inputs.input_events(&button(1, Button::X, true));
+        inputs.input_events(&axis(0, Axis::LeftX, i16::MAX));
+        inputs.input_events(&axis(0, Axis::LeftY, 0));
+
+        let mut released = inputs.input_events(&Event::ControllerDeviceRemoved {
+            timestamp: 0,
+            which: 0,
+        });
+        released.sort_by_key(|ev| ev.action as usize);
+        assert_eq!(
+            released,
+            [
+                InputEvent::analog(Action::Right, 0.0).with_source(InputSource::Stick(0)),
+                InputEvent::new(Action::Jump, ActionState::Inactive)
+                    .with_source(InputSource::Button(0)),
+            ]
+        );
+
+        // O outro controle continua segurando o que estava apertado
+        assert_eq!(
+            inputs.input_events(&button(1, Button::X, false)),
+            [InputEvent::new(Action::Attack, ActionState::Inactive)
+                .with_source(InputSource::Button(1))]
+        );
+        assert!(inputs.input_events(&button(0, Button::A, false)).is_empty());
+    }
+
+    #[test]
+    fn stick_events_only_when_strength_changes() {
+        let mut inputs = GamepadInputs::default();
+        assert!(inputs.input_events(&axis(0, Axis::LeftY, 100)).is_empty());
+        assert_eq!(
+            inputs.input_events(&axis(0, Axis::LeftY, i16::MIN)),
+            [
+                InputEvent::analog(Action::Up, 1.0).with_source(InputSource::Stick(0)),
+                InputEvent::analog(Action::Down, 0.0).with_source(InputSource::Stick(0)),
+            ]
+        );
+        assert!(inputs
+            .input_events(&axis(0, Axis::LeftY, i16::MIN))
+            .is_empty());
+        assert!(inputs
+            .input_events(&axis(0, Axis::RightX, i16::MAX))
+            .is_empty());
+    }
+}
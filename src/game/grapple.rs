@@ -0,0 +1,137 @@
+use super::{
+    components::{CollisionLayers, Facing, Normal, Player, Position, Rectangle, Side, Velocity},
+    input::{Action, Cursor, InputState},
+    physics::WALKABLE_NORMAL_Y,
+    projectile::Aim,
+    spatial::{CastFilter, SpatialQuery},
+};
+use bevy_ecs::{
+    change_detection::Res,
+    entity::Entity,
+    prelude::{Component, Query},
+    query::With,
+};
+use log::debug;
+use serde::Deserialize;
+
+/// Ponto em que a corda está presa
+#[derive(Debug, Clone)]
+pub struct Anchor {
+    pub point: Position,
+    /// Distância máxima entre o centro do player e o ponto
+    pub length: f64,
+}
+
+/// Gancho que prende o player no primeiro estático na direção da mira enquanto
+/// [Action::Grapple] estiver apertado
+#[derive(Debug, Clone, Component, Deserialize)]
+#[serde(default)]
+pub struct Grapple {
+    pub aim: Aim,
+    /// Alcance do gancho, em pixels
+    pub range: f64,
+    #[serde(skip)]
+    anchor: Option<Anchor>,
+}
+
+impl Default for Grapple {
+    fn default() -> Self {
+        Self {
+            aim: Aim::Facing,
+            range: 400.0,
+            anchor: None,
+        }
+    }
+}
+
+impl Grapple {
+    pub fn anchor(&self) -> Option<&Anchor> {
+        self.anchor.as_ref()
+    }
+}
+
+type Grapplers<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Position,
+        &'static Rectangle,
+        &'static Facing,
+        &'static mut Grapple,
+        Option<&'static CollisionLayers>,
+    ),
+    With<Player>,
+>;
+
+/// Lança o gancho quando [Action::Grapple] é apertado e solta quando ele é liberado. Soltar não
+/// muda a velocidade, então o player continua com o embalo do balanço
+pub fn fire_grapple(
+    mut query: Grapplers,
+    spatial: SpatialQuery,
+    inputs: Res<InputState>,
+    cursor: Res<Cursor>,
+) {
+    let fire = inputs.just_pressed(Action::Grapple);
+    let release = inputs.just_released(Action::Grapple);
+    for (entity, position, rectangle, facing, mut grapple, layers) in query.iter_mut() {
+        if release {
+            grapple.anchor = None;
+        }
+        if !fire || grapple.anchor.is_some() || !inputs.active(Action::Grapple) {
+            continue;
+        }
+
+        let center = rectangle.on_position(position).center();
+        // Mirando pelo lado o gancho vai na diagonal, senão só prenderia em paredes
+        let direction = match (grapple.aim, facing.0) {
+            (Aim::Facing, Side::Left) => Normal::new(-1.0, 1.0),
+            (Aim::Facing, Side::Right) => Normal::new(1.0, 1.0),
+            (Aim::Mouse, _) => {
+                let cursor = &cursor.position;
+                Normal::new(cursor.x - center.x, cursor.y - center.y)
+            }
+        };
+        let filter = CastFilter {
+            layers: layers.copied(),
+            only_static: true,
+            ..CastFilter::excluding(entity)
+        };
+        let hit = spatial.cast_ray(&center, direction, grapple.range, &filter);
+        // Preso no chão a corda só puxaria o player para baixo
+        if let Some(hit) = hit.filter(|hit| hit.normal.y() < WALKABLE_NORMAL_Y) {
+            debug!("Grapple attached to {:?}", hit.entity);
+            grapple.anchor = Some(Anchor {
+                point: hit.point,
+                length: hit.distance,
+            });
+        }
+    }
+}
+
+/// Mantém o player dentro do comprimento da corda. Só a velocidade na direção da corda é tirada, a
+/// gravidade e o [move_system](super::physics::move_system) fazem o resto do pêndulo
+pub fn apply_rope(mut query: Query<(&mut Position, &Rectangle, &mut Velocity, &Grapple)>) {
+    for (mut position, rectangle, mut velocity, grapple) in query.iter_mut() {
+        let Some(anchor) = &grapple.anchor else {
+            continue;
+        };
+        let center = rectangle.on_position(&position).center();
+        let (dx, dy) = (center.x - anchor.point.x, center.y - anchor.point.y);
+        let distance = (dx.powi(2) + dy.powi(2)).sqrt();
+        if distance <= anchor.length {
+            continue;
+        }
+
+        let outward = Normal::new(dx, dy);
+        let excess = distance - anchor.length;
+        position.x -= outward.x() * excess;
+        position.y -= outward.y() * excess;
+
+        let speed_out = velocity.x * outward.x() + velocity.y * outward.y();
+        if speed_out > 0.0 {
+            velocity.x -= speed_out * outward.x();
+            velocity.y -= speed_out * outward.y();
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use bevy_ecs::{entity::Entity, system::Resource};
+use serde_json::Value;
+
+/// Quantas alterações o [History] guarda, as mais antigas são esquecidas
+const MAX_HISTORY: usize = 200;
+
+/// Uma alteração numa entidade do mapa, guardada pelo JSON de antes e de depois. `None` é a
+/// entidade não existir, então criar é `None -> Some` e apagar é `Some -> None`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// Entidade que tem o estado atual. Desfazer e refazer recriam a entidade, então isso muda
+    pub entity: Entity,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl Change {
+    /// A mesma alteração no sentido contrário
+    pub fn inverse(self) -> Self {
+        Self {
+            entity: self.entity,
+            before: self.after,
+            after: self.before,
+        }
+    }
+}
+
+/// Pilhas de desfazer e refazer do editor
+#[derive(Debug, Default, Resource)]
+pub struct History {
+    undo: Vec<Change>,
+    redo: Vec<Change>,
+}
+
+impl History {
+    /// Guarda uma alteração nova. Depois dela não tem mais o que refazer
+    pub fn record(&mut self, change: Change) {
+        self.redo.clear();
+        self.undo.push(change);
+        if self.undo.len() > MAX_HISTORY {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Tira a última alteração para desfazer, já invertida. Depois de aplicada ela deve voltar
+    /// com [History::undone]
+    pub fn undo(&mut self) -> Option<Change> {
+        self.undo.pop().map(Change::inverse)
+    }
+
+    pub fn undone(&mut self, change: Change) {
+        self.redo.push(change.inverse());
+    }
+
+    /// Tira a última alteração desfeita para refazer. Depois de aplicada ela deve voltar com
+    /// [History::redone]
+    pub fn redo(&mut self) -> Option<Change> {
+        self.redo.pop()
+    }
+
+    pub fn redone(&mut self, change: Change) {
+        self.undo.push(change);
+    }
+
+    /// Troca `from` por `to` em todas as alterações, para quando uma entidade é recriada
+    pub fn remap(&mut self, from: Entity, to: Entity) {
+        for change in self.undo.iter_mut().chain(self.redo.iter_mut()) {
+            if change.entity == from {
+                change.entity = to;
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn change(entity: u32, before: Option<Value>, after: Option<Value>) -> Change {
+        Change {
+            entity: Entity::from_raw(entity),
+            before,
+            after,
+        }
+    }
+
+    #[test]
+    fn undoes_and_redoes_in_order() {
+        let (a, b) = (json!({ "x": 1 }), json!({ "x": 2 }));
+        let mut history = History::default();
+        history.record(change(1, None, Some(a.clone())));
+        history.record(change(1, Some(a.clone()), Some(b.clone())));
+
+        let undo = history.undo().unwrap();
+        assert_eq!(undo, change(1, Some(b.clone()), Some(a.clone())));
+        history.undone(undo);
+
+        let redo = history.redo().unwrap();
+        assert_eq!(redo, change(1, Some(a.clone()), Some(b.clone())));
+        history.redone(redo);
+
+        // Uma alteração nova descarta o que dava para refazer
+        let undo = history.undo().unwrap();
+        history.undone(undo);
+        history.record(change(2, None, Some(b)));
+        assert_eq!(history.redo(), None);
+    }
+
+    #[test]
+    fn remaps_recreated_entities() {
+        let mut history = History::default();
+        history.record(change(1, None, Some(json!({}))));
+        history.record(change(3, None, Some(json!({}))));
+        history.remap(Entity::from_raw(1), Entity::from_raw(2));
+
+        assert_eq!(history.undo().unwrap().entity, Entity::from_raw(3));
+        assert_eq!(history.undo().unwrap().entity, Entity::from_raw(2));
+    }
+}
@@ -1,15 +1,15 @@
-use super::components::{Componentable, Position, Rectangle, Solid};
+use super::{camera::Camera, components::Position};
 use bevy_ecs::{
-    event::{EventReader, EventWriter, Events},
-    prelude::Commands,
-    system::{Local, ResMut, Resource},
+    event::{EventReader, EventRegistry, EventWriter},
+    system::{Local, Res, ResMut, Resource},
     world::World,
 };
 use enum_map::EnumMap;
-use sdl2::{event::Event, keyboard::Keycode, pixels::Color};
-use std::cmp::{max, min};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ActionState {
     #[default]
     Inactive,
@@ -25,109 +25,178 @@ impl ActionState {
     }
 }
 
-#[derive(Debug, enum_map::Enum, Eq, PartialEq, Copy, Clone)]
+#[derive(Debug, enum_map::Enum, Eq, PartialEq, Hash, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Action {
     Up,
     Down,
     Left,
     Right,
     Attack,
+    Dash,
+    Pound,
+    Jump,
+    Detonate,
+    Grapple,
 }
 
-impl TryFrom<Keycode> for Action {
-    type Error = Keycode;
-
-    fn try_from(keycode: Keycode) -> Result<Self, Self::Error> {
-        let action = match keycode {
-            Keycode::Left => Self::Left,
-            Keycode::Right => Self::Right,
-            Keycode::Down => Self::Down,
-            Keycode::Up => Self::Up,
-            Keycode::A => Self::Attack,
-            _ => return Err(keycode),
-        };
-        Ok(action)
-    }
+/// De onde veio um [InputEvent]. Cada fonte aperta e solta as ações sem interferir nas outras
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputSource {
+    #[default]
+    Keyboard,
+    /// Botões do controle com esse id
+    Button(u32),
+    /// Analógico do controle com esse id
+    Stick(u32),
 }
 
-#[derive(Debug, bevy_ecs::event::Event)]
+#[derive(Debug, Clone, Copy, PartialEq, bevy_ecs::event::Event, Serialize, Deserialize)]
 pub struct InputEvent {
     pub action: Action,
     pub state: ActionState,
+    /// De 0 a 1. Teclas e botões são sempre 0 ou 1, o analógico fica no meio
+    pub strength: f64,
+    #[serde(default)]
+    pub source: InputSource,
 }
 
-impl TryFrom<Event> for InputEvent {
-    type Error = Event;
-
-    fn try_from(event: Event) -> Result<Self, Self::Error> {
-        match event {
-            Event::KeyDown {
-                keycode: Some(keycode),
-                ..
-            } => keycode
-                .try_into()
-                .map(|action| Self {
-                    action,
-                    state: ActionState::Active,
-                })
-                .map_err(|_| event),
-            Event::KeyUp {
-                keycode: Some(keycode),
-                ..
-            } => keycode
-                .try_into()
-                .map(|action| Self {
-                    action,
-                    state: ActionState::Inactive,
-                })
-                .map_err(|_| event),
-            _ => Err(event),
+impl InputEvent {
+    pub fn new(action: Action, state: ActionState) -> Self {
+        let strength = if state.active() { 1.0 } else { 0.0 };
+        Self {
+            action,
+            state,
+            strength,
+            source: InputSource::Keyboard,
         }
     }
+
+    /// Entrada analógica, ativa enquanto `strength` for maior que zero
+    pub fn analog(action: Action, strength: f64) -> Self {
+        let state = if strength > 0.0 {
+            ActionState::Active
+        } else {
+            ActionState::Inactive
+        };
+        Self {
+            action,
+            state,
+            strength,
+            source: InputSource::Keyboard,
+        }
+    }
+
+    pub fn with_source(self, source: InputSource) -> Self {
+        Self { source, ..self }
+    }
 }
 
 #[derive(Debug, Resource, Default)]
 pub struct InputState {
-    state: EnumMap<Action, ActionState>,
+    /// Força de cada fonte que está apertando a ação agora
+    sources: EnumMap<Action, HashMap<InputSource, f64>>,
+    pressed: EnumMap<Action, bool>,
+    released: EnumMap<Action, bool>,
 }
 
 impl InputState {
-    pub fn state(&self) -> &EnumMap<Action, ActionState> {
-        &self.state
+    /// Se alguma fonte está apertando a ação
+    pub fn active(&self, action: Action) -> bool {
+        !self.sources[action].is_empty()
+    }
+
+    /// Se a ação está apertada por uma tecla ou botão, sem contar o analógico
+    pub fn digital(&self, action: Action) -> bool {
+        self.sources[action]
+            .keys()
+            .any(|source| !matches!(source, InputSource::Stick(_)))
+    }
+
+    /// Quanto a ação está apertada, de 0 a 1. Com mais de uma fonte vale a mais forte
+    pub fn strength(&self, action: Action) -> f64 {
+        self.sources[action].values().copied().fold(0.0, f64::max)
+    }
+
+    /// Se a ação foi apertada nesse frame. Continua valendo mesmo se ela foi solta no mesmo frame
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.pressed[action]
+    }
+
+    /// Se a ação foi solta nesse frame
+    pub fn just_released(&self, action: Action) -> bool {
+        self.released[action]
+    }
+
+    /// Limpa o que só vale para o frame anterior
+    fn next_frame(&mut self) {
+        self.pressed = EnumMap::default();
+        self.released = EnumMap::default();
+    }
+
+    fn apply(&mut self, ev: &InputEvent) {
+        let was_active = self.active(ev.action);
+        let sources = &mut self.sources[ev.action];
+        if ev.state.active() {
+            sources.insert(ev.source, ev.strength);
+        } else {
+            sources.remove(&ev.source);
+        }
+        match (was_active, self.active(ev.action)) {
+            (false, true) => self.pressed[ev.action] = true,
+            (true, false) => self.released[ev.action] = true,
+            _ => {}
+        }
     }
 }
 
+/// Tem que rodar antes de todo sistema que lê o [InputState]
 pub fn update_input_state(
     mut ev_input: EventReader<InputEvent>,
     mut input_state: ResMut<InputState>,
 ) {
+    input_state.next_frame();
     for ev in ev_input.read() {
-        input_state.state[ev.action] = ev.state;
+        input_state.apply(ev);
     }
 }
 
-#[derive(Debug, bevy_ecs::event::Event, Copy, Clone)]
-pub struct MousePress {
-    x: i32,
-    y: i32,
+/// Botão do mouse apertado, na posição do mundo
+#[derive(Debug, bevy_ecs::event::Event, Clone)]
+pub struct MousePress(pub Position);
+
+/// Botão do mouse solto, na posição do mundo
+#[derive(Debug, bevy_ecs::event::Event, Clone)]
+pub struct MouseLift(pub Position);
+
+/// Última posição do mouse na janela, com o y para cima como no mundo
+#[derive(Debug, Resource, Default)]
+pub struct MousePosition {
+    pub x: i32,
+    pub y: i32,
 }
 
-impl MousePress {
+impl MousePosition {
     pub fn new(x: i32, y: i32) -> Self {
         Self { x, y }
     }
+
+    pub fn to_world(&self, camera: &Camera) -> Position {
+        Position::new(camera.pos.x + self.x as f64, camera.pos.y + self.y as f64)
+    }
 }
 
-#[derive(Debug, bevy_ecs::event::Event, Copy, Clone)]
-pub struct MouseLift {
-    x: i32,
-    y: i32,
+/// Onde o mouse está no mundo. Atualizado todo frame, então acompanha a câmera mesmo com o mouse
+/// parado
+#[derive(Debug, Resource, Default)]
+pub struct Cursor {
+    pub position: Position,
 }
 
-impl MouseLift {
-    pub fn new(x: i32, y: i32) -> Self {
-        Self { x, y }
-    }
+/// Usa a câmera do frame anterior, que é a que está desenhada na tela
+pub fn update_cursor(mut cursor: ResMut<Cursor>, mouse: Res<MousePosition>, camera: Res<Camera>) {
+    cursor.position = mouse.to_world(&camera);
 }
 
 #[derive(Debug, bevy_ecs::event::Event)]
@@ -143,34 +212,86 @@ pub fn handle_mouse(
     mut mouse_command: EventWriter<MouseCommand>,
 ) {
     for press in ev_pressed.read() {
-        last_press.replace(*press);
+        last_press.replace(press.clone());
     }
     for lift in ev_lift.read() {
         if let Some(press) = last_press.take() {
-            mouse_command.send(MouseCommand { press, lift: *lift });
+            mouse_command.send(MouseCommand {
+                press,
+                lift: lift.clone(),
+            });
         }
     }
 }
 
 pub fn insert_mouse_resources(world: &mut World) {
-    world.insert_resource(Events::<MousePress>::default());
-    world.insert_resource(Events::<MouseLift>::default());
-    world.insert_resource(Events::<MouseCommand>::default());
+    EventRegistry::register_event::<MousePress>(world);
+    EventRegistry::register_event::<MouseLift>(world);
+    EventRegistry::register_event::<MouseCommand>(world);
+    world.insert_resource(MousePosition::default());
+    world.insert_resource(Cursor::default());
 }
 
-pub fn insert_mouse_square(mut mouse_commands: EventReader<MouseCommand>, mut commands: Commands) {
-    for mouse_command in mouse_commands.read() {
-        let min_x = min(mouse_command.lift.x, mouse_command.press.x);
-        let min_y = min(mouse_command.lift.y, mouse_command.press.y);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_edges() {
+        let mut input = InputState::default();
+
+        input.next_frame();
+        input.apply(&InputEvent::new(Action::Jump, ActionState::Active));
+        assert!(input.just_pressed(Action::Jump));
+        assert!(input.active(Action::Jump));
+
+        input.next_frame();
+        assert!(!input.just_pressed(Action::Jump));
+        assert!(input.active(Action::Jump));
+
+        input.next_frame();
+        input.apply(&InputEvent::new(Action::Jump, ActionState::Inactive));
+        assert!(input.just_released(Action::Jump));
+        assert!(!input.active(Action::Jump));
+
+        // Apertar e soltar no mesmo frame ainda conta como apertado
+        input.next_frame();
+        input.apply(&InputEvent::new(Action::Attack, ActionState::Active));
+        input.apply(&InputEvent::new(Action::Attack, ActionState::Inactive));
+        assert!(input.just_pressed(Action::Attack));
+        assert!(input.just_released(Action::Attack));
+        assert!(!input.active(Action::Attack));
+    }
+
+    #[test]
+    fn analog_changes_are_not_presses() {
+        let mut input = InputState::default();
+        input.next_frame();
+        input.apply(&InputEvent::analog(Action::Right, 0.3));
+        input.next_frame();
+        input.apply(&InputEvent::analog(Action::Right, 0.8));
+        assert!(!input.just_pressed(Action::Right));
+        assert_eq!(input.strength(Action::Right), 0.8);
+    }
+
+    #[test]
+    fn sources_are_merged() {
+        let stick = InputSource::Stick(0);
+        let mut input = InputState::default();
+        input.next_frame();
+        input.apply(&InputEvent::new(Action::Right, ActionState::Active));
+        input.apply(&InputEvent::analog(Action::Right, 0.5).with_source(stick));
+        assert_eq!(input.strength(Action::Right), 1.0);
 
-        let max_x = max(mouse_command.lift.x, mouse_command.press.x);
-        let max_y = max(mouse_command.lift.y, mouse_command.press.y);
+        // O analógico voltando para o centro não solta a tecla
+        input.next_frame();
+        input.apply(&InputEvent::analog(Action::Right, 0.0).with_source(stick));
+        assert!(input.active(Action::Right));
+        assert!(!input.just_released(Action::Right));
+        assert_eq!(input.strength(Action::Right), 1.0);
 
-        commands.spawn((
-            Position::new(min_x, min_y),
-            Rectangle::new((max_x - min_x) as u32, (max_y - min_y) as u32),
-            Color::CYAN.into_fill(),
-            Solid::all(),
-        ));
+        input.apply(&InputEvent::analog(Action::Up, 0.7).with_source(stick));
+        assert!(input.active(Action::Up));
+        assert!(!input.digital(Action::Up));
     }
 }
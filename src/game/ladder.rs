@@ -0,0 +1,107 @@
+use super::{
+    components::{Player, Velocity},
+    input::{Action, InputState},
+    physics::{Collision, Contacts},
+    player::Jump,
+};
+use bevy_ecs::{
+    change_detection::Res,
+    entity::Entity,
+    event::EventReader,
+    prelude::{Component, Query},
+    query::{Has, With},
+    system::Commands,
+};
+use serde::Deserialize;
+use std::collections::HashSet;
+
+/// Área sem colisão em que o player pode subir e descer
+#[derive(Debug, Component)]
+pub struct Ladder;
+
+/// Marca quem está preso numa escada, [gravitate](super::physics::gravitate) ignora essas
+/// entidades
+#[derive(Debug, Component)]
+pub struct Climbing;
+
+/// Como o player sobe escadas
+#[derive(Debug, Clone, Component, Deserialize)]
+#[serde(default)]
+pub struct Climber {
+    /// Velocidade vertical com [Action::Up] ou [Action::Down], em pixels por segundo
+    pub speed: f64,
+    /// Escadas que o player está tocando
+    #[serde(skip)]
+    ladders: HashSet<Entity>,
+}
+
+impl Default for Climber {
+    fn default() -> Self {
+        Self {
+            speed: 250.0,
+            ladders: HashSet::new(),
+        }
+    }
+}
+
+impl Climber {
+    pub fn on_ladder(&self) -> bool {
+        !self.ladders.is_empty()
+    }
+}
+
+pub fn track_ladders(
+    mut climbers: Query<(Entity, &mut Climber)>,
+    ladders: Query<(), With<Ladder>>,
+    mut collisions: EventReader<Collision>,
+) {
+    for collision in collisions.read() {
+        for (entity, mut climber) in climbers.iter_mut() {
+            let Some(ladder) = collision.other(entity).filter(|l| ladders.contains(*l)) else {
+                continue;
+            };
+            if collision.started() {
+                climber.ladders.insert(ladder);
+            } else {
+                climber.ladders.remove(&ladder);
+            }
+        }
+    }
+}
+
+type Climbers<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Climber,
+        &'static mut Velocity,
+        &'static Jump,
+        &'static Contacts,
+        Has<Climbing>,
+    ),
+    With<Player>,
+>;
+
+/// Agarra a escada com [Action::Up] ou [Action::Down] e solta ao pular, ao chegar no chão
+/// descendo ou ao sair dela
+pub fn climb(mut query: Climbers, inputs: Res<InputState>, mut commands: Commands) {
+    let up = inputs.active(Action::Up);
+    let down = inputs.active(Action::Down);
+    for (entity, climber, mut velocity, jump, contacts, climbing) in query.iter_mut() {
+        let release = !climber.on_ladder() || jump.is_jumping() || (down && contacts.ground && !up);
+        if climbing && release {
+            commands.entity(entity).remove::<Climbing>();
+            continue;
+        }
+        if !climbing && (release || !(up || down)) {
+            continue;
+        }
+        if !climbing {
+            commands.entity(entity).insert(Climbing);
+        }
+
+        let direction = up as i8 as f64 - down as i8 as f64;
+        velocity.y = direction * climber.speed;
+    }
+}
@@ -1,12 +1,23 @@
 use super::{
+    abilities::{Abilities, Ability},
     components::{
-        CoinKind, Componentable, Direction, Gravitable, InfiniteArea, KillZone, Player, Position,
-        Rectangle, Solid, Velocity,
+        CoinKind, CollisionLayers, Componentable, Direction, Facing, Gravitable, Health,
+        InfiniteArea, KillZone, Layer, Layers, PhysicsBody, Player, Position, Pushable, Rectangle,
+        Shape, Solid, Velocity,
     },
-    physics::PLAYER_VERTICAL_ACCELERATION,
-    player::Jump,
+    grapple::Grapple,
+    ladder::{Climber, Ladder},
+    physics::{Contacts, GRAVITY},
+    player::{Crouch, Jump, Movement, WallJump},
+    projectile::Weapon,
+    trigger::{Trigger, TriggerAction, TriggerPhase},
+    volume::Volume,
+};
+use bevy_ecs::{
+    entity::Entity as EcsEntity,
+    prelude::{Commands, Component},
+    system::EntityCommands,
 };
-use bevy_ecs::prelude::Commands;
 use sdl2::pixels::Color;
 use serde::{Deserialize, Deserializer};
 use std::str::FromStr;
@@ -19,6 +30,14 @@ impl From<ColorName> for Color {
         value.0
     }
 }
+
+impl ColorName {
+    /// Cores que podem ser usadas no mapa, na ordem em que o editor passa por elas
+    pub const PALETTE: [&str; 9] = [
+        "white", "black", "gray", "red", "green", "blue", "magenta", "yellow", "cyan",
+    ];
+}
+
 impl FromStr for ColorName {
     type Err = String;
 
@@ -51,97 +70,390 @@ impl<'de> Deserialize<'de> for ColorName {
     }
 }
 
+/// Marca as entidades criadas a partir do arquivo do mapa, para serem removidas ao trocar de mapa
+#[derive(Debug, Component)]
+pub struct MapEntity;
+
+/// JSON de onde a entidade foi criada. É o que o editor altera, as posições dos componentes
+/// mudam durante o jogo. O que é salvo fica em [MapSources](super::startup::MapSources)
+#[derive(Debug, Clone, Component)]
+pub struct MapSource(pub serde_json::Value);
+
+/// Área de uma kill zone ou trigger: um retângulo ou um semi-plano infinito
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
-pub enum KillZoneType {
+pub enum AreaType {
     Area {
         position: Position,
         rectangle: Rectangle,
+        /// Camadas afetadas pela área, todas por padrão
+        #[serde(default)]
+        affects: Layers,
     },
     Infinite {
         start: f64,
         direction: Direction,
+        #[serde(default)]
+        affects: Layers,
     },
 }
 
+impl AreaType {
+    fn insert(self, entity: &mut EntityCommands, layer: Layer) {
+        match self {
+            AreaType::Area {
+                position,
+                rectangle,
+                affects,
+            } => entity.insert((
+                position,
+                rectangle,
+                CollisionLayers::new(layer.into(), affects),
+            )),
+            AreaType::Infinite {
+                start,
+                direction,
+                affects,
+            } => entity.insert((
+                InfiniteArea { start, direction },
+                CollisionLayers::new(layer.into(), affects),
+            )),
+        };
+    }
+}
+
+/// Configuração do jogador, tudo opcional no mapa
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PlayerConfig {
+    physics: PhysicsBody,
+    movement: Movement,
+    jump: Jump,
+    wall_jump: WallJump,
+    abilities: Abilities,
+    crouch: Crouch,
+    climber: Climber,
+    weapon: Weapon,
+    grapple: Grapple,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(tag = "entity", rename_all = "snake_case")]
 pub enum Entity {
     Player {
         position: Position,
+        #[serde(flatten)]
+        config: Box<PlayerConfig>,
     },
     Static {
         position: Position,
         rectangle: Rectangle,
         color: ColorName,
+        /// Rampa ou círculo dentro do retângulo, senão o retângulo inteiro é sólido
+        #[serde(default)]
+        shape: Option<Shape>,
     },
     Coin {
         position: Position,
         color: ColorName,
         jump: Option<f64>,
+        /// Habilidade desbloqueada ao pegar a moeda
+        #[serde(default)]
+        ability: Option<Ability>,
+    },
+    KillZone(AreaType),
+    /// Caixa que cai, pode ser empurrada e empilhada
+    Crate {
+        position: Position,
+        #[serde(default = "Entity::crate_rectangle")]
+        rectangle: Rectangle,
+        #[serde(default)]
+        color: Option<ColorName>,
+        #[serde(default)]
+        pushable: Pushable,
+        /// Campos que faltam vêm do [PhysicsBody] do player, não do da caixa
+        #[serde(default = "Entity::crate_physics")]
+        physics: PhysicsBody,
+    },
+    Ladder {
+        position: Position,
+        rectangle: Rectangle,
+        #[serde(default)]
+        color: Option<ColorName>,
+    },
+    /// Água ou gravidade diferente dentro da área
+    Volume {
+        #[serde(flatten)]
+        area: AreaType,
+        volume: Volume,
+        #[serde(default)]
+        color: Option<ColorName>,
+    },
+    Trigger {
+        #[serde(flatten)]
+        area: AreaType,
+        action: TriggerAction,
+        #[serde(default)]
+        on: TriggerPhase,
+        /// Triggers sem cor são invisíveis
+        #[serde(default)]
+        color: Option<ColorName>,
     },
-    KillZone(KillZoneType),
 }
 
 impl Entity {
-    pub fn spawn(self, commands: &mut Commands) {
+    fn crate_rectangle() -> Rectangle {
+        Rectangle::new(40, 40)
+    }
+
+    fn crate_physics() -> PhysicsBody {
+        PhysicsBody::CRATE
+    }
+
+    pub fn spawn(self, commands: &mut Commands) -> EcsEntity {
+        let mut entity = commands.spawn(MapEntity);
+        let id = entity.id();
         match self {
-            Entity::Player { position } => {
-                commands.spawn((
+            Entity::Player { position, config } => {
+                let PlayerConfig {
+                    physics,
+                    movement,
+                    jump,
+                    wall_jump,
+                    abilities,
+                    crouch,
+                    climber,
+                    weapon,
+                    grapple,
+                } = *config;
+                entity.insert((
                     Player,
                     position,
                     Rectangle::new(50, 50),
                     Color::BLUE.into_fill(),
                     Velocity::default(),
+                    physics,
                     Solid::all(),
-                    Jump::default(),
-                    Gravitable,
+                    Gravitable::default(),
+                    Health::new(100),
+                    CollisionLayers::new(Layer::Player.into(), Layers::ALL),
+                ));
+                entity.insert((
+                    movement,
+                    jump,
+                    wall_jump,
+                    abilities,
+                    crouch,
+                    climber,
+                    weapon,
+                    grapple,
+                    Contacts::default(),
+                    Facing::default(),
                 ));
             }
             Entity::Static {
                 position,
                 rectangle,
                 color,
+                shape,
             } => {
-                commands.spawn((
+                entity.insert((
                     position,
                     rectangle,
                     Color::from(color).into_fill(),
                     Solid::all(),
+                    CollisionLayers::new(Layer::Terrain.into(), Layers::ALL),
                 ));
+                if let Some(shape) = shape {
+                    entity.insert(shape);
+                }
             }
             Entity::Coin {
                 position,
                 color,
                 jump,
+                ability,
             } => {
-                let coin_kind = jump
-                    .map(|v| CoinKind::Jump((PLAYER_VERTICAL_ACCELERATION / v) as u32))
-                    .unwrap_or(CoinKind::Color(color.clone().into()));
-                commands.spawn((
+                let coin_kind = match (ability, jump) {
+                    (Some(ability), _) => CoinKind::Ability(ability),
+                    (None, Some(v)) => CoinKind::Jump((GRAVITY / v) as u32),
+                    (None, None) => CoinKind::Color(color.clone().into()),
+                };
+                entity.insert((
                     position,
                     Rectangle::new(10, 10),
                     Color::from(color).into_fill(),
                     coin_kind,
+                    CollisionLayers::new(Layer::Pickup.into(), Layer::Player.into()),
+                ));
+            }
+            Entity::KillZone(area) => {
+                if matches!(area, AreaType::Area { .. }) {
+                    entity.insert(Color::RGBA(255, 0, 0, 64).into_fill());
+                }
+                area.insert(&mut entity, Layer::Hazard);
+                entity.insert((
+                    KillZone,
+                    Trigger::new(TriggerAction::Kill, TriggerPhase::Enter),
+                ));
+            }
+            Entity::Crate {
+                position,
+                rectangle,
+                color,
+                pushable,
+                physics,
+            } => {
+                let color = color.map(Color::from).unwrap_or(Color::RGB(160, 110, 60));
+                entity.insert((
+                    position,
+                    rectangle,
+                    color.into_fill(),
+                    Velocity::default(),
+                    physics,
+                    Solid::all(),
+                    Gravitable::default(),
+                    Contacts::default(),
+                    pushable,
+                    CollisionLayers::new(Layer::Prop.into(), Layers::ALL),
                 ));
             }
-            Entity::KillZone(zone_type) => match zone_type {
-                KillZoneType::Area {
+            Entity::Ladder {
+                position,
+                rectangle,
+                color,
+            } => {
+                let color = color.map(Color::from).unwrap_or(Color::RGB(139, 90, 43));
+                entity.insert((
                     position,
                     rectangle,
-                } => {
-                    commands.spawn((
-                        KillZone,
-                        position,
-                        rectangle,
-                        Color::RGBA(255, 0, 0, 64).into_fill(),
-                    ));
+                    color.into_outline(),
+                    Ladder,
+                    CollisionLayers::new(Layer::Sensor.into(), Layer::Player.into()),
+                ));
+            }
+            Entity::Volume {
+                area,
+                volume,
+                color,
+            } => {
+                area.insert(&mut entity, Layer::Sensor);
+                entity.insert(volume);
+                if let Some(color) = color {
+                    let Color { r, g, b, .. } = color.into();
+                    entity.insert(Color::RGBA(r, g, b, 96).into_fill());
                 }
-                KillZoneType::Infinite { start, direction } => {
-                    let infinite_area = InfiniteArea { start, direction };
-                    commands.spawn((KillZone, infinite_area));
+            }
+            Entity::Trigger {
+                area,
+                action,
+                on,
+                color,
+            } => {
+                area.insert(&mut entity, Layer::Sensor);
+                entity.insert(Trigger::new(action, on));
+                if let Some(color) = color {
+                    let Color { r, g, b, .. } = color.into();
+                    entity.insert(Color::RGBA(r, g, b, 64).into_fill());
                 }
-            },
+            }
         }
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::volume::Water;
+
+    #[test]
+    fn parses_map_file() {
+        let map_file = std::fs::read_to_string("assets/maps/map_01.json").unwrap();
+        let entities = serde_json::from_str::<Vec<Entity>>(&map_file).unwrap();
+        assert!(entities.iter().any(|e| matches!(e, Entity::Player { .. })));
+    }
+
+    #[test]
+    fn parses_player_config() {
+        let player = r#"{
+            "entity": "player",
+            "position": { "x": 0, "y": 0 },
+            "physics": { "gravity_scale": 0.5 }
+        }"#;
+        let Entity::Player { config, .. } = serde_json::from_str(player).unwrap() else {
+            panic!("not a player");
+        };
+        assert_eq!(config.physics.gravity_scale, 0.5);
+        assert_eq!(config.physics.drag, PhysicsBody::default().drag);
+        assert_eq!(
+            config.movement.acceleration(&config.physics, true),
+            config.physics.acceleration
+        );
+    }
+
+    #[test]
+    fn parses_crate_physics() {
+        let Entity::Crate { physics, .. } =
+            serde_json::from_str(r#"{ "entity": "crate", "position": { "x": 0, "y": 0 } }"#)
+                .unwrap()
+        else {
+            panic!("not a crate");
+        };
+        assert_eq!(physics, PhysicsBody::CRATE);
+
+        let heavy = r#"{
+            "entity": "crate",
+            "position": { "x": 0, "y": 0 },
+            "physics": { "gravity_scale": 2.0, "acceleration": 100.0 }
+        }"#;
+        let Entity::Crate { physics, .. } = serde_json::from_str(heavy).unwrap() else {
+            panic!("not a crate");
+        };
+        assert_eq!((physics.gravity_scale, physics.acceleration), (2.0, 100.0));
+    }
+
+    #[test]
+    fn parses_trigger() {
+        let trigger = r#"{
+            "entity": "trigger",
+            "start": -500,
+            "direction": "down",
+            "affects": ["player"],
+            "action": { "type": "set_gravity", "scale": 0.5 },
+            "on": "stay"
+        }"#;
+        let Entity::Trigger {
+            area: AreaType::Infinite { affects, .. },
+            action,
+            on,
+            color: None,
+        } = serde_json::from_str(trigger).unwrap()
+        else {
+            panic!("not an infinite trigger");
+        };
+        assert_eq!(affects, Layer::Player.into());
+        assert_eq!(action, TriggerAction::SetGravity { scale: 0.5 });
+        assert_eq!(on, TriggerPhase::Stay);
+    }
+
+    #[test]
+    fn parses_water_volume() {
+        let volume = r#"{
+            "entity": "volume",
+            "position": { "x": 0, "y": 0 },
+            "rectangle": { "width": 100, "height": 50 },
+            "volume": { "type": "water", "stroke": 300 }
+        }"#;
+        let Entity::Volume {
+            area: AreaType::Area { .. },
+            volume: Volume::Water(water),
+            ..
+        } = serde_json::from_str(volume).unwrap()
+        else {
+            panic!("not a water volume");
+        };
+        assert_eq!(water.stroke, 300.0);
+        assert_eq!(water.max_fall_speed, Water::default().max_fall_speed);
     }
 }
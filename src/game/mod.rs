@@ -1,14 +1,28 @@
 use bevy_ecs::schedule::ScheduleLabel;
 
+pub mod abilities;
+pub mod bindings;
 pub mod camera;
 pub mod components;
 pub mod draw;
+pub mod editor;
+pub mod explosion;
+pub mod gamepad;
+pub mod grapple;
+pub mod history;
 pub mod input;
+pub mod ladder;
 mod map;
 pub mod physics;
 pub mod player;
+pub mod projectile;
+pub mod render;
+pub mod replay;
 pub mod resources;
+pub mod spatial;
 pub mod startup;
+pub mod trigger;
+pub mod volume;
 
 #[derive(Debug, ScheduleLabel, Clone, Eq, Hash, PartialEq)]
 pub struct Update;
@@ -1,33 +1,228 @@
 use super::{
-    components::{Bounce, CollisionAxis, Gravitable, Position, Rectangle, Solid, Velocity},
-    player::Jump,
+    components::{
+        Bounce, CollisionAxis, CollisionLayers, Gravitable, InfiniteArea, Normal, PhysicsBody,
+        Position, Pushable, Rectangle, Shape, Solid, Velocity,
+    },
+    ladder::Climbing,
     resources::Time,
 };
 use bevy_ecs::{
-    prelude::{Query, Res},
-    query::{With, Without},
+    change_detection::Mut,
+    entity::Entity,
+    event::EventWriter,
+    prelude::{Commands, Component, Query, Res},
+    query::{Has, With, Without},
+    system::Local,
 };
+use std::{collections::BTreeSet, time::Duration};
 
-pub const PLAYER_MAX_VERTICAL_SPEED: f64 = 1500.0;
-pub const PLAYER_MAX_HORIZONTAL_SPEED: f64 = 900.0;
-pub const PLAYER_VERTICAL_ACCELERATION: f64 = 5000.0;
-pub const PLAYER_HORIZONTAL_ACCELERATION: f64 = 60.0;
+/// Aceleração da gravidade para um [PhysicsBody] com `gravity_scale` 1
+pub const GRAVITY: f64 = 5000.0;
+/// Componente vertical mínima da normal de uma superfície para ela ser tratada como chão
+pub const WALKABLE_NORMAL_Y: f64 = 0.5;
+
+/// Velocidade dada de fora, como um dash ou uma explosão. Enquanto dura, o [limit_velocity] não
+/// limita a entidade aos máximos do [PhysicsBody]
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Impulse {
+    remaining: Duration,
+}
+
+impl Impulse {
+    pub fn from_millis(millis: u64) -> Self {
+        Self {
+            remaining: Duration::from_millis(millis),
+        }
+    }
+}
+
+/// Lados em que a entidade encostou em algo sólido no último passo de física
+#[derive(Debug, Default, Clone, Copy, Component)]
+pub struct Contacts {
+    pub ground: bool,
+    pub ceiling: bool,
+    pub left: bool,
+    pub right: bool,
+    /// Se estava no chão no passo anterior a esse
+    pub was_grounded: bool,
+}
+
+impl Contacts {
+    /// Registra um contato pelo lado da entidade que encostou
+    pub fn touch(&mut self, axis: CollisionAxis) {
+        match axis {
+            CollisionAxis::Up => self.ceiling = true,
+            CollisionAxis::Down => self.ground = true,
+            CollisionAxis::Left => self.left = true,
+            CollisionAxis::Right => self.right = true,
+        }
+    }
+
+    /// Registra um contato com uma superfície com a normal `normal`
+    pub fn touch_normal(&mut self, normal: Normal) {
+        if normal.y() >= WALKABLE_NORMAL_Y {
+            self.ground = true;
+        } else if normal.y() <= -WALKABLE_NORMAL_Y {
+            self.ceiling = true;
+        } else if normal.x() > 0.0 {
+            self.left = true;
+        } else {
+            self.right = true;
+        }
+    }
+}
+
+/// Esquece os contatos do passo anterior, eles são recalculados pela resolução de colisões
+pub fn clear_contacts(mut query: Query<&mut Contacts>) {
+    for mut contacts in query.iter_mut() {
+        *contacts = Contacts {
+            was_grounded: contacts.ground,
+            ..Default::default()
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionState {
+    Started,
+    Ended,
+}
+
+/// Emitido pelo passo de física quando duas entidades começam ou param de se sobrepor. `a` é sempre
+/// uma entidade com [Velocity]
+#[derive(Debug, Clone, Copy, bevy_ecs::event::Event)]
+pub struct Collision {
+    pub a: Entity,
+    pub b: Entity,
+    pub state: CollisionState,
+}
+
+impl Collision {
+    /// Retorna a outra entidade da colisão, se `entity` fizer parte dela
+    pub fn other(&self, entity: Entity) -> Option<Entity> {
+        if self.a == entity {
+            Some(self.b)
+        } else if self.b == entity {
+            Some(self.a)
+        } else {
+            None
+        }
+    }
+
+    pub fn started(&self) -> bool {
+        self.state == CollisionState::Started
+    }
+}
+
+/// Hitboxes com as camadas de colisão, filtradas por `F`
+type Hitboxes<'w, 's, F = ()> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Position,
+        &'static Rectangle,
+        Option<&'static CollisionLayers>,
+    ),
+    F,
+>;
+
+/// Tudo que tem hitbox, inclusive os estáticos com [Shape]
+type Colliders<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Position,
+        &'static Rectangle,
+        Option<&'static Shape>,
+        Option<&'static CollisionLayers>,
+    ),
+>;
+
+/// Detecta sobreposições de entidades com velocidade com qualquer outra e emite [Collision] quando
+/// elas começam ou terminam
+///
+/// Roda depois de [move_system] e antes da resolução das colisões, senão a resolução separa as
+/// hitboxes e o chão nunca seria detectado
+pub fn detect_collisions(
+    moving: Hitboxes<With<Velocity>>,
+    others: Colliders,
+    areas: Query<(Entity, &InfiniteArea, Option<&CollisionLayers>)>,
+    mut contacts: Local<BTreeSet<(Entity, Entity)>>,
+    mut collisions: EventWriter<Collision>,
+) {
+    // Ordenados, para os eventos saírem sempre na mesma ordem e os replays se repetirem
+    let mut current = BTreeSet::new();
+    for (a, pos, rect, layers) in moving.iter() {
+        let hitbox = rect.on_position(pos);
+        for (b, pos, rect, shape, other_layers) in others.iter() {
+            // Pares de duas entidades com velocidade só são considerados uma vez
+            if a == b
+                || (moving.contains(b) && b < a)
+                || !CollisionLayers::interacts(layers, other_layers)
+            {
+                continue;
+            }
+            let other_hitbox = rect.on_position(pos);
+            let overlaps = match shape {
+                Some(shape) => shape.contact(&other_hitbox, &hitbox).is_some(),
+                None => hitbox.colides_with(&other_hitbox),
+            };
+            if overlaps {
+                current.insert((a, b));
+            }
+        }
+        for (b, area, other_layers) in areas.iter() {
+            if CollisionLayers::interacts(layers, other_layers) && area.collides_with(&hitbox) {
+                current.insert((a, b));
+            }
+        }
+    }
+
+    for &(a, b) in current.difference(&contacts) {
+        collisions.send(Collision {
+            a,
+            b,
+            state: CollisionState::Started,
+        });
+    }
+    for &(a, b) in contacts.difference(&current) {
+        collisions.send(Collision {
+            a,
+            b,
+            state: CollisionState::Ended,
+        });
+    }
+    *contacts = current;
+}
+
+/// Coisas sólidas com velocidade, que são barradas pelos estáticos
+type SolidMovers<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Position,
+        &'static Rectangle,
+        &'static mut Velocity,
+        Option<&'static mut Contacts>,
+        Option<&'static CollisionLayers>,
+    ),
+    With<Solid>,
+>;
 
 /// Colisão entre coisas com e sem velocidade.
 ///
 /// Não dá para fazer todas as colisões aqui porque elas dão overlap, e isso deixa o borrow checker
 /// mto puto. Fazer numa query só não dá porque nem tudo tem velocidade e tentar fazer uma sub-query
 /// usando Query::transmute_lens_filtered também deixa o borrow checker puto
-pub fn handle_collision_moving_static(
-    mut query_moving: Query<
-        (&mut Position, &Rectangle, &mut Velocity, Option<&mut Jump>),
-        With<Solid>,
-    >,
-    mut query_static: Query<(&Position, &Rectangle), (With<Solid>, Without<Velocity>)>,
-) {
-    for (mut pos, rec, mut vel, mut jump) in query_moving.iter_mut() {
+pub fn handle_collision_moving_static(mut query_moving: SolidMovers, query_static: Statics) {
+    for (mut pos, rec, mut vel, mut contacts, layers) in query_moving.iter_mut() {
         let mut hitbox = rec.on_position_mut(&mut pos);
-        for (pos, rec) in query_static.iter_mut() {
+        for (pos, rec, static_layers) in query_static.iter() {
+            if !CollisionLayers::interacts(layers, static_layers) {
+                continue;
+            }
             let static_hitbox = rec.on_position(pos);
             if let Some(axis) = hitbox.colides_with_axis(&static_hitbox) {
                 match axis {
@@ -38,9 +233,6 @@ pub fn handle_collision_moving_static(
                     CollisionAxis::Down => {
                         vel.y = 0.0;
                         hitbox.pos.y = static_hitbox.top();
-                        if let Some(jump) = &mut jump {
-                            jump.grounded = true;
-                        }
                     }
                     CollisionAxis::Left => {
                         vel.x = 0.0;
@@ -51,32 +243,39 @@ pub fn handle_collision_moving_static(
                         hitbox.pos.x = static_hitbox.left() - hitbox.rect.width as f64;
                     }
                 }
+                if let Some(contacts) = &mut contacts {
+                    contacts.touch(axis);
+                }
             }
         }
     }
 }
 
+type Bouncers<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Position,
+        &'static Rectangle,
+        &'static mut Velocity,
+        &'static Bounce,
+        Option<&'static CollisionLayers>,
+    ),
+    With<Solid>,
+>;
+
 //TODO this could probably be implemented inside handle_collision_moving_static as the code is basicaly equal.
-pub fn handle_bounce_moving_static(
-    mut query_moving: Query<
-        (
-            &mut Position,
-            &Rectangle,
-            &mut Velocity,
-            Option<&mut Jump>,
-            &Bounce,
-        ),
-        With<Solid>,
-    >,
-    mut query_static: Query<(&Position, &Rectangle), (With<Solid>, Without<Velocity>)>,
-) {
-    for (mut pos, rec, mut vel, mut jump, mut bounce) in query_moving.iter_mut() {
+pub fn handle_bounce_moving_static(mut query_moving: Bouncers, query_static: Statics) {
+    for (mut pos, rec, mut vel, bounce, layers) in query_moving.iter_mut() {
         if !bounce.enabled {
             println!("not bounced");
             continue;
         }
         let mut hitbox = rec.on_position_mut(&mut pos);
-        for (pos, rec) in query_static.iter_mut() {
+        for (pos, rec, static_layers) in query_static.iter() {
+            if !CollisionLayers::interacts(layers, static_layers) {
+                continue;
+            }
             let static_hitbox = rec.on_position(pos);
             if let Some(axis) = hitbox.colides_with_axis(&static_hitbox) {
                 println!("bounced yeahhh!");
@@ -105,32 +304,371 @@ pub fn handle_bounce_moving_static(
     }
 }
 
-pub fn gravitate(mut query: Query<&mut Velocity, With<Gravitable>>, time: Res<Time>) {
+/// Coisas sólidas com velocidade, com o que muda a resposta à colisão com um [Shape]
+type ShapeMovers<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Position,
+        &'static Rectangle,
+        &'static mut Velocity,
+        Option<&'static mut Contacts>,
+        Option<&'static Bounce>,
+        Option<&'static CollisionLayers>,
+    ),
+    With<Solid>,
+>;
+
+/// Estáticos com [Shape]
+type Shapes<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Position,
+        &'static Rectangle,
+        &'static Shape,
+        Option<&'static CollisionLayers>,
+    ),
+    (With<Solid>, Without<Velocity>),
+>;
+
+/// Colisão entre coisas com velocidade e estáticos com [Shape]
+///
+/// A resposta usa a normal da superfície: quem tem [Bounce] é refletido nela, quem encosta numa
+/// superfície andável para de cair e o resto desliza ao longo dela
+pub fn handle_collision_moving_shapes(
+    mut query_moving: ShapeMovers,
+    query_static: Shapes,
+    time: Res<Time>,
+) {
     let delta = time.delta().as_secs_f64();
-    for mut velocity in query.iter_mut() {
-        if velocity.y > -PLAYER_MAX_VERTICAL_SPEED {
-            velocity.y -= PLAYER_VERTICAL_ACCELERATION * delta;
+    for (mut pos, rec, mut vel, mut contacts, bounce, layers) in query_moving.iter_mut() {
+        let mut hitbox = rec.on_position_mut(&mut pos);
+        for (pos, rec, shape, static_layers) in query_static.iter() {
+            if !CollisionLayers::interacts(layers, static_layers) {
+                continue;
+            }
+            let shape_hitbox = rec.on_position(pos);
+            let Some(contact) = shape.contact(&shape_hitbox, &hitbox) else {
+                // Quem está descendo uma rampa é mantido nela em vez de sair voando
+                let Some(contacts) = &mut contacts else {
+                    continue;
+                };
+                if let Some(gap) = shape.gap_below(&shape_hitbox, &hitbox) {
+                    let snap = vel.x.abs() * delta * rec.height as f64 / rec.width as f64 + 1.0;
+                    if contacts.was_grounded && vel.y <= 0.0 && gap > 0.0 && gap <= snap {
+                        hitbox.pos.y -= gap;
+                        vel.y = 0.0;
+                        contacts.ground = true;
+                    }
+                }
+                continue;
+            };
+
+            hitbox.pos.x += contact.push.0;
+            hitbox.pos.y += contact.push.1;
+
+            let normal = contact.normal;
+            if let Some(contacts) = &mut contacts {
+                contacts.touch_normal(normal);
+            }
+            let into_surface = vel.x * normal.x() + vel.y * normal.y();
+            match bounce.filter(|bounce| bounce.enabled) {
+                Some(bounce) if into_surface < 0.0 => {
+                    vel.x -= (1.0 + bounce.bounciness) * into_surface * normal.x();
+                    vel.y -= (1.0 + bounce.bounciness) * into_surface * normal.y();
+                }
+                Some(_) => (),
+                None if normal.y() >= WALKABLE_NORMAL_Y => {
+                    vel.y = vel.y.max(0.0);
+                }
+                None if into_surface < 0.0 => {
+                    vel.x -= into_surface * normal.x();
+                    vel.y -= into_surface * normal.y();
+                }
+                None => (),
+            }
         }
     }
 }
 
-pub fn limit_velocity(mut query: Query<&mut Velocity>) {
-    const NEGATIVE_PLAYER_MAX_VERTICAL_SPEED: f64 = -PLAYER_MAX_VERTICAL_SPEED;
-    const NEGATIVE_PLAYER_MAX_HORIZONTAL_SPEED: f64 = -PLAYER_MAX_HORIZONTAL_SPEED;
-    for mut velocity in query.iter_mut() {
-        velocity.y = match velocity.y {
-            PLAYER_MAX_VERTICAL_SPEED.. => PLAYER_MAX_VERTICAL_SPEED,
-            ..NEGATIVE_PLAYER_MAX_VERTICAL_SPEED => NEGATIVE_PLAYER_MAX_VERTICAL_SPEED,
-            _ => velocity.y,
+/// Distância até um estático em que uma entidade já é considerada encostada nele
+const CONTACT_MARGIN: f64 = 0.5;
+
+/// Estáticos retangulares, os que as entidades empurradas não podem atravessar
+type Statics<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Position,
+        &'static Rectangle,
+        Option<&'static CollisionLayers>,
+    ),
+    (With<Solid>, Without<Velocity>, Without<Shape>),
+>;
+
+/// Uma das entidades na colisão entre duas entidades com velocidade
+struct Mover<'a> {
+    pos: Mut<'a, Position>,
+    rect: &'a Rectangle,
+    vel: Mut<'a, Velocity>,
+    contacts: Option<Mut<'a, Contacts>>,
+    pushable: bool,
+    bounce: Option<&'a Bounce>,
+    layers: Option<&'a CollisionLayers>,
+}
+
+impl Mover<'_> {
+    fn touch(&mut self, axis: CollisionAxis) {
+        if let Some(contacts) = &mut self.contacts {
+            contacts.touch(axis);
+        }
+    }
+
+    /// Se tem algo sólido encostado no lado `axis`
+    fn blocked(&self, axis: CollisionAxis) -> bool {
+        self.contacts.as_ref().is_some_and(|c| match axis {
+            CollisionAxis::Up => c.ceiling,
+            CollisionAxis::Down => c.ground,
+            CollisionAxis::Left => c.left,
+            CollisionAxis::Right => c.right,
+        })
+    }
+
+    /// Se tem um estático encostado no lado `axis`. Os [Contacts] só marcam o que a entidade
+    /// atravessou, então não pegam uma caixa parada bem encostada numa parede
+    fn against_static(&self, axis: CollisionAxis, statics: &Statics) -> bool {
+        let (dx, dy) = match axis {
+            CollisionAxis::Up => (0.0, CONTACT_MARGIN),
+            CollisionAxis::Down => (0.0, -CONTACT_MARGIN),
+            CollisionAxis::Left => (-CONTACT_MARGIN, 0.0),
+            CollisionAxis::Right => (CONTACT_MARGIN, 0.0),
         };
-        velocity.x = match velocity.x {
-            PLAYER_MAX_HORIZONTAL_SPEED.. => PLAYER_MAX_HORIZONTAL_SPEED,
-            ..NEGATIVE_PLAYER_MAX_HORIZONTAL_SPEED => NEGATIVE_PLAYER_MAX_HORIZONTAL_SPEED,
-            _ => velocity.x,
+        let probe = Position::new(self.pos.x + dx, self.pos.y + dy);
+        let hitbox = self.rect.on_position(&probe);
+        statics.iter().any(|(pos, rect, layers)| {
+            CollisionLayers::interacts(self.layers, layers)
+                && hitbox.colides_with(&rect.on_position(pos))
+        })
+    }
+
+    /// Tira a entidade de dentro de `other`, sendo `axis` o lado dela que está encostando
+    fn push_out(&mut self, other: &Position, other_rect: &Rectangle, axis: CollisionAxis) {
+        let other = other_rect.on_position(other);
+        match axis {
+            CollisionAxis::Up => self.pos.y = other.bottom() - self.rect.height as f64,
+            CollisionAxis::Down => self.pos.y = other.top(),
+            CollisionAxis::Left => self.pos.x = other.right(),
+            CollisionAxis::Right => self.pos.x = other.left() - self.rect.width as f64,
+        }
+    }
+
+    fn bounce_off(&mut self, other: &Mover, axis: CollisionAxis, bounciness: f64) {
+        self.push_out(&other.pos, other.rect, axis);
+        match axis {
+            CollisionAxis::Up | CollisionAxis::Down => {
+                self.vel.y = other.vel.y + (other.vel.y - self.vel.y) * bounciness;
+            }
+            CollisionAxis::Left | CollisionAxis::Right => {
+                self.vel.x = other.vel.x + (other.vel.x - self.vel.x) * bounciness;
+            }
+        }
+    }
+}
+
+/// Resolve a colisão de `a` com `b`, sendo `axis` o lado de `a` que encostou em `b`
+///
+/// Quem está em cima sempre fica apoiado em quem está embaixo, a não ser que não tenha espaço para
+/// subir. Na horizontal quem é [Pushable] é empurrado, e leva a velocidade de quem empurrou. Se
+/// ninguém pode ser empurrado, quem está indo na direção do outro para
+fn resolve_movers<'a>(
+    a: &mut Mover<'a>,
+    b: &mut Mover<'a>,
+    axis: CollisionAxis,
+    statics: &Statics,
+) {
+    if let Some(bounce) = a.bounce.filter(|b| b.enabled) {
+        a.bounce_off(b, axis, bounce.bounciness);
+        return;
+    }
+    if let Some(bounce) = b.bounce.filter(|b| b.enabled) {
+        b.bounce_off(a, axis.opposite(), bounce.bounciness);
+        return;
+    }
+
+    match axis {
+        CollisionAxis::Up | CollisionAxis::Down => {
+            let (upper, lower) = if axis == CollisionAxis::Down {
+                (a, b)
+            } else {
+                (b, a)
+            };
+            if upper.blocked(CollisionAxis::Up) && !lower.blocked(CollisionAxis::Down) {
+                lower.push_out(&upper.pos, upper.rect, CollisionAxis::Up);
+                lower.vel.y = lower.vel.y.min(upper.vel.y);
+            } else {
+                upper.push_out(&lower.pos, lower.rect, CollisionAxis::Down);
+                upper.vel.y = upper.vel.y.max(lower.vel.y);
+            }
+            upper.touch(CollisionAxis::Down);
+            lower.touch(CollisionAxis::Up);
+        }
+        CollisionAxis::Left | CollisionAxis::Right => {
+            let a_blocked =
+                a.blocked(axis.opposite()) || a.against_static(axis.opposite(), statics);
+            let b_blocked = b.blocked(axis) || b.against_static(axis, statics);
+            let a_free = a.pushable && !a_blocked;
+            let b_free = b.pushable && !b_blocked;
+            let direction = if axis == CollisionAxis::Right {
+                1.0
+            } else {
+                -1.0
+            };
+            // Velocidade de cada um na direção do outro
+            let (a_speed, b_speed) = (a.vel.x * direction, -b.vel.x * direction);
+            let a_moves = match (a_free, b_free, a_blocked, b_blocked) {
+                (true, false, ..) => true,
+                (false, true, ..) => false,
+                // Sai do caminho quem está indo mais devagar
+                (true, true, ..) => a_speed < b_speed,
+                // Quem está encostado em algo não sai do lugar
+                (false, false, true, false) => false,
+                (false, false, false, true) => true,
+                (false, false, ..) => a_speed >= b_speed,
+            };
+            let (moved, pusher, side) = if a_moves {
+                (a, b, axis)
+            } else {
+                (b, a, axis.opposite())
+            };
+            moved.push_out(&pusher.pos, pusher.rect, side);
+            if a_free || b_free {
+                moved.vel.x = pusher.vel.x;
+            } else {
+                moved.vel.x = 0.0;
+            }
+            moved.touch(side);
+            pusher.touch(side.opposite());
         }
     }
 }
 
+/// Coisas sólidas com velocidade que podem empurrar umas às outras, ver [Mover]
+type Pushers<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Position,
+        &'static Rectangle,
+        &'static mut Velocity,
+        Option<&'static mut Contacts>,
+        Has<Pushable>,
+        Option<&'static Bounce>,
+        Option<&'static CollisionLayers>,
+    ),
+    With<Solid>,
+>;
+
+/// Colisão entre duas coisas com velocidade, assim elas podem ser empurradas e servir de chão
+///
+/// Roda depois da colisão com os estáticos, que marca nos [Contacts] quem não tem para onde ir
+pub fn handle_collision_moving_moving(mut query: Pushers, statics: Statics) {
+    let mut combinations = query.iter_combinations_mut();
+    while let Some([a, b]) = combinations.fetch_next() {
+        let (pos, rect, vel, contacts, pushable, bounce, layers) = a;
+        let mut a = Mover {
+            pos,
+            rect,
+            vel,
+            contacts,
+            pushable,
+            bounce,
+            layers,
+        };
+        let (pos, rect, vel, contacts, pushable, bounce, layers) = b;
+        let mut b = Mover {
+            pos,
+            rect,
+            vel,
+            contacts,
+            pushable,
+            bounce,
+            layers,
+        };
+        if !CollisionLayers::interacts(a.layers, b.layers) {
+            continue;
+        }
+        let axis = a
+            .rect
+            .on_position(&a.pos)
+            .colides_with_axis(&b.rect.on_position(&b.pos));
+        if let Some(axis) = axis {
+            resolve_movers(&mut a, &mut b, axis, &statics);
+        }
+    }
+}
+
+/// Freia no chão o que é [Pushable], senão uma caixa empurrada desliza para sempre
+pub fn apply_friction(mut query: Query<(&mut Velocity, &Pushable, &Contacts)>, time: Res<Time>) {
+    let delta = time.delta().as_secs_f64();
+    for (mut velocity, pushable, contacts) in query.iter_mut() {
+        if contacts.ground {
+            velocity.x =
+                velocity.x.signum() * (velocity.x.abs() - pushable.friction * delta).max(0.0);
+        }
+    }
+}
+
+pub fn gravitate(
+    mut query: Query<(&mut Velocity, &PhysicsBody, &Gravitable), Without<Climbing>>,
+    time: Res<Time>,
+) {
+    let delta = time.delta().as_secs_f64();
+    for (mut velocity, body, gravitable) in query.iter_mut() {
+        let acceleration = GRAVITY * body.gravity_scale * gravitable.scale;
+        // Com a gravidade invertida "cair" é subir
+        let fall = acceleration.signum();
+        if velocity.y * fall > -body.max_vertical_speed {
+            velocity.y -= acceleration * delta;
+        }
+        if let Some(max_fall) = gravitable.max_fall_speed {
+            velocity.y = fall * (velocity.y * fall).max(-max_fall);
+        }
+    }
+}
+
+pub fn apply_drag(mut query: Query<(&mut Velocity, &PhysicsBody)>, time: Res<Time>) {
+    let delta = time.delta().as_secs_f64();
+    for (mut velocity, body) in query.iter_mut() {
+        let factor = (1.0 - body.drag * delta).max(0.0);
+        velocity.x *= factor;
+        velocity.y *= factor;
+    }
+}
+
+/// Limita a velocidade aos máximos do [PhysicsBody], menos durante um [Impulse]
+pub fn limit_velocity(
+    mut query: Query<(Entity, &mut Velocity, &PhysicsBody, Option<&mut Impulse>)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut velocity, body, impulse) in query.iter_mut() {
+        if let Some(mut impulse) = impulse {
+            impulse.remaining = impulse.remaining.saturating_sub(time.delta());
+            if !impulse.remaining.is_zero() {
+                continue;
+            }
+            commands.entity(entity).remove::<Impulse>();
+        }
+        velocity.x = velocity
+            .x
+            .clamp(-body.max_horizontal_speed, body.max_horizontal_speed);
+        velocity.y = velocity
+            .y
+            .clamp(-body.max_vertical_speed, body.max_vertical_speed);
+    }
+}
+
 pub fn move_system(mut query: Query<(&mut Position, &Velocity)>, time: Res<Time>) {
     let delta = time.delta().as_secs_f64();
     for (mut pos, vel) in query.iter_mut() {
@@ -138,3 +676,68 @@ pub fn move_system(mut query: Query<(&mut Position, &Velocity)>, time: Res<Time>
         pos.y += vel.y * delta;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::{
+        schedule::{IntoSystemConfigs, Schedule},
+        world::World,
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn player_does_not_push_crate_into_wall() {
+        let mut world = World::new();
+        let mut time = Time::fixed(Duration::from_millis(16));
+        time.update();
+        world.insert_resource(time);
+        world.spawn((Position::new(100, 0), Rectangle::new(50, 100), Solid::all()));
+        let crate_ = world
+            .spawn((
+                Position::new(60, 0),
+                Rectangle::new(40, 40),
+                Velocity::default(),
+                Contacts::default(),
+                Pushable::default(),
+                Solid::all(),
+            ))
+            .id();
+        let player = world
+            .spawn((
+                Position::new(10, 0),
+                Rectangle::new(50, 50),
+                Velocity::default(),
+                Contacts::default(),
+                Solid::all(),
+            ))
+            .id();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(
+            (
+                move_system,
+                clear_contacts,
+                handle_collision_moving_static,
+                handle_collision_moving_moving,
+            )
+                .chain(),
+        );
+        for _ in 0..10 {
+            world.get_mut::<Velocity>(player).unwrap().x = 600.0;
+            schedule.run(&mut world);
+
+            let crate_x = world.get::<Position>(crate_).unwrap().x;
+            let player = world.get::<Position>(player).unwrap().x;
+            assert!(
+                crate_x + 40.0 <= 100.0,
+                "crate pushed into the wall at {crate_x}"
+            );
+            assert!(
+                player + 50.0 <= crate_x,
+                "player inside the crate at {player}"
+            );
+        }
+        assert_eq!(world.get::<Velocity>(player).unwrap().x, 0.0);
+    }
+}
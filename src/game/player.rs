@@ -1,198 +1,460 @@
 use super::{
+    abilities::{Abilities, Ability},
     components::{
-        hitbox::{IntoHitbox, ToHitbox, ToHitboxMut},
-        Bounce, Bullet, BulletBundle, CoinKind, Colorable, Componentable, KillZone, Normal, Player,
-        Position, Rectangle, Solid, Velocity,
-    },
-    input::{Action, InputEvent, InputState},
-    physics::{
-        PLAYER_HORIZONTAL_ACCELERATION, PLAYER_MAX_HORIZONTAL_SPEED, PLAYER_MAX_VERTICAL_SPEED,
-        PLAYER_VERTICAL_ACCELERATION,
+        CoinKind, CollisionLayers, Colorable, Facing, Normal, PhysicsBody, Player, Position,
+        Rectangle, Side, Velocity,
     },
+    input::{Action, InputState},
+    ladder::{Climber, Climbing},
+    physics::{Collision, Contacts, GRAVITY},
     resources::{Spawn, Time},
+    spatial::{CastFilter, SpatialQuery},
 };
 use crate::game::camera::Camera;
-use crate::game::components::hitbox::HitboxOwnedWithVelocity;
-use crate::game::components::{Hitbox, InfiniteArea};
-use bevy_ecs::system::ResMut;
 use bevy_ecs::{
     change_detection::Res,
     entity::Entity,
     event::EventReader,
     prelude::{Component, Query},
-    query::{With, Without},
-    system::Commands,
-};
-use log::{debug, info};
-use std::{
-    cmp::Ordering::{Equal, Greater, Less},
-    time::Duration,
+    query::{Has, With, Without},
+    system::ParamSet,
 };
+use log::debug;
+use serde::Deserialize;
+use std::time::Duration;
 
 const JUMP_MILLIS: u64 = 500;
+/// Fração do `acceleration` do [PhysicsBody] usada no ar quando o [Movement] não define outra
+const AIR_CONTROL: f64 = 2.0 / 3.0;
 
-pub fn handle_player_input(
-    mut query: Query<(&mut Velocity, &mut Jump), With<Player>>,
-    inputs: Res<InputState>,
-) {
-    for (mut velocity, mut jump) in query.iter_mut() {
-        if inputs.state()[Action::Left].active() && velocity.x >= -PLAYER_MAX_HORIZONTAL_SPEED {
-            velocity.x -= PLAYER_HORIZONTAL_ACCELERATION;
+/// Como o player acelera e freia. Todos os valores são em pixels por segundo²
+#[derive(Debug, Clone, Copy, Component, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Movement {
+    /// Sem valor usa o `acceleration` do [PhysicsBody]
+    pub ground_acceleration: Option<f64>,
+    /// Sem valor usa uma fração do `acceleration` do [PhysicsBody]
+    pub air_acceleration: Option<f64>,
+    /// Desaceleração no chão quando não tem input
+    pub ground_friction: f64,
+    pub air_friction: f64,
+    /// Aceleração usada quando o input é contrário à velocidade atual
+    pub turn_around: f64,
+    /// Aceleração para baixo com [Action::Down]
+    pub fast_fall: f64,
+}
+
+impl Movement {
+    /// Aceleração do input no chão ou no ar, vinda do [PhysicsBody] quando não foi configurada
+    pub fn acceleration(&self, body: &PhysicsBody, grounded: bool) -> f64 {
+        if grounded {
+            self.ground_acceleration.unwrap_or(body.acceleration)
+        } else {
+            self.air_acceleration
+                .unwrap_or(body.acceleration * AIR_CONTROL)
         }
-        if inputs.state()[Action::Right].active() && velocity.x <= PLAYER_MAX_HORIZONTAL_SPEED {
-            velocity.x += PLAYER_HORIZONTAL_ACCELERATION;
+    }
+}
+
+impl Default for Movement {
+    fn default() -> Self {
+        Self {
+            ground_acceleration: None,
+            air_acceleration: None,
+            ground_friction: 3600.0,
+            air_friction: 1200.0,
+            turn_around: 7200.0,
+            fast_fall: 600.0,
         }
+    }
+}
+
+/// O que o input do player move diretamente
+type PlayerControls<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Velocity,
+        &'static mut Jump,
+        &'static mut Facing,
+        &'static Contacts,
+        &'static PhysicsBody,
+        &'static Movement,
+        Option<&'static Crouch>,
+        Option<&'static Climber>,
+        Has<Climbing>,
+    ),
+    With<Player>,
+>;
 
-        if !inputs.state()[Action::Left].active() && !inputs.state()[Action::Right].active() {
-            match velocity.x.total_cmp(&0.0) {
-                Less => velocity.x += PLAYER_HORIZONTAL_ACCELERATION,
-                Greater => velocity.x -= PLAYER_HORIZONTAL_ACCELERATION,
-                Equal => (),
+pub fn handle_player_input(mut query: PlayerControls, inputs: Res<InputState>, time: Res<Time>) {
+    let delta = time.delta().as_secs_f64();
+    for (mut velocity, mut jump, mut facing, contacts, body, movement, crouch, climber, climbing) in
+        query.iter_mut()
+    {
+        // Com o analógico a aceleração é proporcional a quanto ele está inclinado
+        let direction = inputs.strength(Action::Right) - inputs.strength(Action::Left);
+        let posture = crouch.map(Crouch::posture).unwrap_or_default();
+        let max_speed = match (posture, crouch) {
+            (Posture::Crouching, Some(crouch)) => body.max_horizontal_speed * crouch.speed_factor,
+            _ => body.max_horizontal_speed,
+        };
+
+        if let (Posture::Sliding, Some(crouch)) = (posture, crouch) {
+            // Deslizando o input não muda a velocidade, só o atrito do slide freia
+            velocity.x =
+                velocity.x.signum() * (velocity.x.abs() - crouch.slide_friction * delta).max(0.0);
+        } else if direction != 0.0 {
+            facing.0 = if direction < 0.0 {
+                Side::Left
+            } else {
+                Side::Right
+            };
+            let acceleration = if velocity.x * direction < 0.0 {
+                movement.turn_around
+            } else {
+                movement.acceleration(body, contacts.ground)
+            };
+            // Só limita o que o input adiciona. Durante um Impulse, como um dash ou uma
+            // explosão, a velocidade passa do máximo e o input não freia ela
+            if velocity.x * direction < max_speed {
+                velocity.x =
+                    (velocity.x + direction * acceleration * delta).clamp(-max_speed, max_speed);
             }
+        } else {
+            let friction = if contacts.ground {
+                movement.ground_friction
+            } else {
+                movement.air_friction
+            };
+            velocity.x = velocity.x.signum() * (velocity.x.abs() - friction * delta).max(0.0);
         }
 
-        if inputs.state()[Action::Up].active() && velocity.y <= PLAYER_MAX_VERTICAL_SPEED {
-            jump.do_jump(&mut velocity);
-        } else {
-            jump.clear_jump()
+        // Numa escada [Action::Up] sobe, então só [Action::Jump] pula. Inclinar o analógico
+        // para cima também não pula, senão seria impossível mirar ou andar sem pular
+        let on_ladder = climber.is_some_and(Climber::on_ladder);
+        let up = inputs.digital(Action::Up) && !on_ladder;
+        let can_jump = velocity.y <= body.max_vertical_speed;
+        jump.update(
+            (inputs.just_pressed(Action::Jump) || (up && inputs.just_pressed(Action::Up)))
+                && can_jump,
+            (inputs.active(Action::Jump) || up) && can_jump,
+            contacts.ground || climbing,
+            &mut velocity,
+            body,
+        );
+
+        // No chão [Action::Down] agacha, ver [crouch]
+        if inputs.active(Action::Down)
+            && !contacts.ground
+            && !climbing
+            && velocity.y >= -body.max_vertical_speed
+        {
+            velocity.y -= movement.fast_fall * delta;
         }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Posture {
+    #[default]
+    Standing,
+    Crouching,
+    /// Agachou correndo, mantém o embalo até ficar devagar
+    Sliding,
+}
 
-        if inputs.state()[Action::Down].active() && velocity.y >= -PLAYER_MAX_VERTICAL_SPEED {
-            velocity.y -= 10.0;
+/// Agachar e deslizar com [Action::Down]
+#[derive(Debug, Clone, Copy, Component, Deserialize)]
+#[serde(default)]
+pub struct Crouch {
+    /// Altura do [Rectangle] agachado
+    pub height: u32,
+    /// Fração da velocidade horizontal máxima andando agachado
+    pub speed_factor: f64,
+    /// Velocidade horizontal mínima para agachar virar slide
+    pub slide_speed: f64,
+    /// Desaceleração durante o slide, em pixels por segundo²
+    pub slide_friction: f64,
+    #[serde(skip)]
+    posture: Posture,
+    /// Altura de pé, guardada enquanto está agachado
+    #[serde(skip)]
+    standing_height: Option<u32>,
+}
+
+impl Default for Crouch {
+    fn default() -> Self {
+        Self {
+            height: 25,
+            speed_factor: 0.4,
+            slide_speed: 500.0,
+            slide_friction: 900.0,
+            posture: Posture::Standing,
+            standing_height: None,
         }
     }
 }
 
-pub fn player_attack(
-    player_query: Query<(&Position, &Rectangle), With<Player>>,
-    mut player_velocity: Query<&mut Velocity, With<Player>>,
-    mut commands: Commands,
-    mut ev_input: EventReader<InputEvent>,
-    query2: Query<(Entity, &Position, &Rectangle), With<Bullet>>,
-) {
-    let player_hitbox = player_query.single().hitbox();
-    let mut player_velocity = player_velocity.single_mut();
-    for ev in ev_input.read() {
-        if ev.state.active() && ev.action == Action::Attack {
-            let p_center = player_hitbox.center();
-            if let Some(bullet_entity) = query2.iter().next() {
-                let b_center = bullet_entity.hitbox().center();
-                let distance =
-                    ((p_center.x - b_center.x).powi(2) + (p_center.y - b_center.y).powi(2)).sqrt();
-
-                let vector = Normal::new(p_center.x - b_center.x, p_center.y - b_center.y);
-                if distance < 200.0 {
-                    player_velocity.x = vector.x() * 2.0f64.powf(20.0 - distance / 10.0);
-                    player_velocity.y = vector.y() * 2.0f64.powf(20.0 - distance / 10.0);
-                }
-
-                commands.entity(bullet_entity.0).despawn(); // Despawn the first matching entity
-
-                println!("Bullet exploded !");
-            } else {
-                commands.spawn(BulletBundle {
-                    marker: Bullet,
-                    position: Position::new(p_center.x + 30.0, p_center.y),
-                    velocity: Velocity::new(60, 0),
-                    rectangle: Rectangle::new(10, 10),
-                    solid: Solid::all(),
-                    color: sdl2::pixels::Color::RED.into_fill(),
-                    bounce: Bounce::new(true, 1.0),
-                });
-                println!("No bullet entity found to explode.");
+impl Crouch {
+    pub fn posture(&self) -> Posture {
+        self.posture
+    }
+}
+
+type Crouchers<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut Crouch,
+        &'static mut Rectangle,
+        &'static Position,
+        &'static Velocity,
+        &'static Contacts,
+        &'static PhysicsBody,
+        Option<&'static CollisionLayers>,
+    ),
+    With<Player>,
+>;
+
+/// Agacha ou desliza enquanto [Action::Down] está apertado no chão, e só levanta se tiver espaço
+/// em cima
+pub fn crouch(mut set: ParamSet<(Crouchers, SpatialQuery)>, inputs: Res<InputState>) {
+    let down = inputs.active(Action::Down);
+    let mut standing_up = Vec::new();
+    for (entity, mut crouch, mut rectangle, _, velocity, contacts, body, _) in set.p0().iter_mut() {
+        let crouch_speed = body.max_horizontal_speed * crouch.speed_factor;
+        match crouch.posture {
+            Posture::Standing if down && contacts.ground => {
+                crouch.standing_height = Some(rectangle.height);
+                rectangle.height = crouch.height.min(rectangle.height);
+                crouch.posture = if velocity.x.abs() >= crouch.slide_speed {
+                    Posture::Sliding
+                } else {
+                    Posture::Crouching
+                };
             }
+            Posture::Standing => {}
+            _ if !down => standing_up.push(entity),
+            Posture::Sliding if velocity.x.abs() <= crouch_speed || !contacts.ground => {
+                crouch.posture = Posture::Crouching;
+            }
+            _ => {}
+        }
+    }
+
+    for entity in standing_up {
+        let Some((standing_height, rectangle, position, layers)) =
+            set.p0()
+                .get(entity)
+                .ok()
+                .map(|(_, crouch, rect, pos, .., layers)| {
+                    let standing_height = crouch.standing_height.unwrap_or(rect.height);
+                    (standing_height, *rect, pos.clone(), layers.copied())
+                })
+        else {
+            continue;
         };
+        let room = standing_height.saturating_sub(rectangle.height) as f64;
+        let filter = CastFilter {
+            layers,
+            ..CastFilter::excluding(entity)
+        };
+        let ceiling = set
+            .p1()
+            .cast_rect(&rectangle, &position, Normal::new(0.0, 1.0), room, &filter)
+            .is_some();
+        if ceiling {
+            continue;
+        }
+
+        if let Ok((_, mut crouch, mut rectangle, ..)) = set.p0().get_mut(entity) {
+            rectangle.height = standing_height;
+            crouch.standing_height = None;
+            crouch.posture = Posture::Standing;
+        }
     }
 }
 
 pub fn player_collides_coin(
-    mut player: Query<(&mut Colorable, &Position, &Rectangle, &mut Velocity), With<Player>>,
-    mut coins: Query<(&CoinKind, &Position, &Rectangle), Without<Player>>,
+    mut player: Query<(Entity, &mut Colorable, &mut Velocity, &mut Abilities), With<Player>>,
+    coins: Query<&CoinKind, Without<Player>>,
+    mut collisions: EventReader<Collision>,
 ) {
-    let (mut player_color, pos, rect, mut vel) = player.single_mut();
-    let player_hitbox = rect.on_position(pos);
-    for (kind, pos, rect) in coins.iter_mut() {
-        let hitbox = rect.on_position(pos);
-        if player_hitbox.colides_with(&hitbox) {
-            match kind {
-                CoinKind::Color(color) => player_color.color = *color,
-                CoinKind::Jump(amount) => vel.y = *amount as f64,
+    let (player_entity, mut player_color, mut vel, mut abilities) = player.single_mut();
+    for collision in collisions.read().filter(|c| c.started()) {
+        let Some(kind) = collision
+            .other(player_entity)
+            .and_then(|coin| coins.get(coin).ok())
+        else {
+            continue;
+        };
+        match kind {
+            CoinKind::Color(color) => player_color.color = *color,
+            CoinKind::Jump(amount) => vel.y = *amount as f64,
+            CoinKind::Ability(ability) => {
+                debug!("Unlocked {:?}", ability);
+                abilities.unlock(*ability);
             }
         }
     }
 }
 
-#[derive(Debug, Component, Default)]
+#[derive(Debug, Component, Deserialize)]
+#[serde(default)]
 pub struct Jump {
+    /// Por quanto tempo depois de sair do chão ainda dá para pular
+    pub coyote_millis: u64,
+    /// Por quanto tempo um pulo apertado antes de encostar no chão fica guardado
+    pub buffer_millis: u64,
+    #[serde(skip)]
     time_to_jump: Option<Duration>,
-    pub grounded: bool,
+    /// Tempo desde a última vez que estava no chão, `None` depois de pular
+    #[serde(skip)]
+    since_grounded: Option<Duration>,
+    /// Tempo desde que o pulo foi apertado, enquanto ele ainda não foi usado
+    #[serde(skip)]
+    buffered: Option<Duration>,
+}
+
+impl Default for Jump {
+    fn default() -> Self {
+        Self {
+            coyote_millis: 100,
+            buffer_millis: 100,
+            time_to_jump: None,
+            since_grounded: None,
+            buffered: None,
+        }
+    }
 }
 
 impl Jump {
-    fn update_velocity(&self, velocity: &mut Velocity) {
+    fn update_velocity(&self, velocity: &mut Velocity, body: &PhysicsBody) {
         if let Some(time) = self.time_to_jump {
             let max_jump_time = Duration::from_millis(JUMP_MILLIS);
             velocity.y = (max_jump_time - (max_jump_time - time / 2)).as_secs_f64()
-                * PLAYER_VERTICAL_ACCELERATION;
+                * GRAVITY
+                * body.gravity_scale;
         }
     }
 
-    pub fn do_jump(&mut self, vel: &mut Velocity) {
-        match (self.grounded, self.time_to_jump) {
-            (true, None) => {
-                self.grounded = false;
-                self.time_to_jump = Some(Duration::from_millis(JUMP_MILLIS));
-                self.update_velocity(vel);
-            }
-            (false, Some(v)) if v > Duration::ZERO => {
-                self.update_velocity(vel);
-            }
-            _ => (),
-        }
+    /// Começa um pulo, esteja onde estiver
+    pub fn start(&mut self, vel: &mut Velocity, body: &PhysicsBody) {
+        self.since_grounded = None;
+        self.buffered = None;
+        self.time_to_jump = Some(Duration::from_millis(JUMP_MILLIS));
+        self.update_velocity(vel, body);
     }
 
-    pub fn clear_jump(&mut self) {
-        self.time_to_jump = None;
+    pub fn is_jumping(&self) -> bool {
+        self.time_to_jump.is_some()
+    }
+
+    /// Consome um pulo apertado recentemente que não foi usado para pular do chão
+    pub fn take_buffered(&mut self) -> bool {
+        let buffer = Duration::from_millis(self.buffer_millis);
+        self.buffered.take().is_some_and(|t| t <= buffer)
+    }
+
+    /// Começa, continua ou termina o pulo de acordo com o input do frame. `pressed` só vale no
+    /// frame em que o pulo foi apertado, `holding` enquanto ele continua apertado
+    pub fn update(
+        &mut self,
+        pressed: bool,
+        holding: bool,
+        grounded: bool,
+        vel: &mut Velocity,
+        body: &PhysicsBody,
+    ) {
+        if grounded {
+            self.since_grounded = Some(Duration::ZERO);
+        }
+        if pressed {
+            self.buffered = Some(Duration::ZERO);
+        }
+
+        let coyote = Duration::from_millis(self.coyote_millis);
+        let buffer = Duration::from_millis(self.buffer_millis);
+        let can_jump =
+            self.time_to_jump.is_none() && self.since_grounded.is_some_and(|t| t <= coyote);
+        let wants_jump = self.buffered.is_some_and(|t| t <= buffer);
+
+        if can_jump && wants_jump {
+            self.start(vel, body);
+        } else if !holding {
+            self.time_to_jump = None;
+        } else if self.time_to_jump.is_some_and(|t| t > Duration::ZERO) {
+            self.update_velocity(vel, body);
+        }
     }
 }
 
-pub fn update_jump_time(mut query: Query<&mut Jump>, time: Res<Time>) {
-    for mut jump in query.iter_mut() {
-        if let Some(v) = &mut jump.time_to_jump {
-            *v = v.saturating_sub(time.delta());
+/// Pulo e deslize na parede, liberados pela habilidade [Ability::WallJump]
+#[derive(Debug, Clone, Copy, Component, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct WallJump {
+    /// Velocidade máxima de queda encostado numa parede
+    pub slide_speed: f64,
+    /// Velocidade horizontal do pulo, para longe da parede
+    pub kick_horizontal: f64,
+    pub kick_vertical: f64,
+}
+
+impl Default for WallJump {
+    fn default() -> Self {
+        Self {
+            slide_speed: 150.0,
+            kick_horizontal: 700.0,
+            kick_vertical: 1100.0,
         }
     }
 }
 
-pub fn player_enter_kill_zone(
-    mut player_query: Query<(&mut Position, &Rectangle, &mut Velocity), With<Player>>,
-    mut kill_zone_query: Query<(&Position, &Rectangle), (With<KillZone>, Without<Player>)>,
-    mut kill_zone_infinite_query: Query<
-        (&InfiniteArea),
-        (With<KillZone>, Without<Player>, Without<Rectangle>),
-    >,
-    spawn: Res<Spawn>,
-    mut camera: ResMut<Camera>,
+pub fn wall_slide_and_jump(
+    mut query: Query<(&mut Velocity, &mut Jump, &Contacts, &WallJump, &Abilities), With<Player>>,
 ) {
-    let mut player_hitbox = player_query.single_mut().into_hitbox();
-    for (position, rectangle) in kill_zone_query.iter() {
-        let kill_zone_hitbox = rectangle.on_position(position);
-        if player_hitbox.colides_with(&kill_zone_hitbox) {
-            debug!("Player killed by KillZone");
-            *player_hitbox.pos = spawn.0.clone();
-            *player_hitbox.velocity = Velocity::default();
-            camera.pos = Position::new(0, 0);
-            break;
+    for (mut velocity, mut jump, contacts, wall_jump, abilities) in query.iter_mut() {
+        if !abilities.has(Ability::WallJump)
+            || contacts.ground
+            || !(contacts.left || contacts.right)
+        {
+            continue;
+        }
+
+        if jump.take_buffered() {
+            let away = if contacts.left { 1.0 } else { -1.0 };
+            velocity.x = away * wall_jump.kick_horizontal;
+            velocity.y = wall_jump.kick_vertical;
+        } else if velocity.y < -wall_jump.slide_speed {
+            velocity.y = -wall_jump.slide_speed;
         }
     }
+}
 
-    for infinite_area in kill_zone_infinite_query.iter() {
-        if infinite_area.collides_with(&player_hitbox) {
-            debug!("Player killed by InfiniteArea");
-            *player_hitbox.pos = spawn.0.clone();
-            *player_hitbox.velocity = Velocity::default();
-            camera.pos = Position::new(0, 0);
-            break;
+pub fn update_jump_time(mut query: Query<&mut Jump>, time: Res<Time>) {
+    for mut jump in query.iter_mut() {
+        if let Some(v) = &mut jump.time_to_jump {
+            *v = v.saturating_sub(time.delta());
+        }
+        if let Some(v) = &mut jump.since_grounded {
+            *v += time.delta();
+        }
+        if let Some(v) = &mut jump.buffered {
+            *v += time.delta();
         }
     }
 }
+
+/// Leva o player de volta para o [Spawn]
+pub fn respawn(
+    position: &mut Position,
+    velocity: &mut Velocity,
+    spawn: &Spawn,
+    camera: &mut Camera,
+) {
+    *position = spawn.0.clone();
+    *velocity = Velocity::default();
+    camera.pos = Position::new(0, 0);
+}
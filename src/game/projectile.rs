@@ -0,0 +1,211 @@
+use super::{
+    components::{
+        Bounce, Bullet, BulletBundle, CollisionLayers, Componentable, Facing, Layer, Layers,
+        Normal, PhysicsBody, Player, Position, Rectangle, Side, Solid, Velocity,
+    },
+    explosion::{Blast, Explosion, Explosive},
+    input::{Action, Cursor, InputState},
+    physics::Collision,
+    resources::Time,
+};
+use bevy_ecs::{
+    change_detection::Res,
+    entity::Entity,
+    event::{EventReader, EventWriter},
+    prelude::{Component, Query},
+    query::{With, Without},
+    system::Commands,
+};
+use log::debug;
+use serde::Deserialize;
+use std::time::Duration;
+
+const PROJECTILE_SIZE: u32 = 10;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Aim {
+    /// Atira para o lado em que o player está virado
+    #[default]
+    Facing,
+    /// Atira na direção do mouse
+    Mouse,
+}
+
+/// Como o player atira com [Action::Attack] e detona com [Action::Detonate]
+#[derive(Debug, Clone, Copy, Component, Deserialize)]
+#[serde(default)]
+pub struct Weapon {
+    pub aim: Aim,
+    /// Velocidade do projétil, em pixels por segundo
+    pub speed: f64,
+    /// Quantos projéteis do player podem existir ao mesmo tempo
+    pub max_count: usize,
+    pub cooldown_millis: u64,
+    /// Projéteis somem depois desse tempo
+    pub lifetime_millis: Option<u64>,
+    /// Projéteis somem quando batem numa parede depois de quicar esse número de vezes
+    pub max_bounces: Option<u32>,
+    pub blast: Blast,
+    #[serde(skip)]
+    cooldown: Duration,
+}
+
+impl Default for Weapon {
+    fn default() -> Self {
+        Self {
+            aim: Aim::Facing,
+            speed: 600.0,
+            max_count: 3,
+            cooldown_millis: 250,
+            lifetime_millis: Some(3000),
+            max_bounces: Some(5),
+            blast: Blast::default(),
+            cooldown: Duration::ZERO,
+        }
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct Projectile {
+    pub owner: Entity,
+    age: Duration,
+    lifetime: Option<Duration>,
+    max_bounces: Option<u32>,
+    hits: u32,
+}
+
+pub fn fire_projectiles(
+    mut shooters: Query<(Entity, &Position, &Rectangle, &Facing, &mut Weapon), With<Player>>,
+    projectiles: Query<&Projectile>,
+    inputs: Res<InputState>,
+    cursor: Res<Cursor>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let fire = inputs.just_pressed(Action::Attack);
+    for (owner, position, rectangle, facing, mut weapon) in shooters.iter_mut() {
+        weapon.cooldown = weapon.cooldown.saturating_sub(time.delta());
+        let count = projectiles.iter().filter(|p| p.owner == owner).count();
+        if !fire || !weapon.cooldown.is_zero() || count >= weapon.max_count {
+            continue;
+        }
+        weapon.cooldown = Duration::from_millis(weapon.cooldown_millis);
+
+        let center = rectangle.on_position(position).center();
+        let facing = match facing.0 {
+            Side::Left => Normal::new(-1.0, 0.0),
+            Side::Right => Normal::new(1.0, 0.0),
+        };
+        let direction = match weapon.aim {
+            Aim::Facing => facing,
+            Aim::Mouse => {
+                let cursor = &cursor.position;
+                let direction = Normal::new(cursor.x - center.x, cursor.y - center.y);
+                if direction.x() == 0.0 && direction.y() == 0.0 {
+                    facing
+                } else {
+                    direction
+                }
+            }
+        };
+
+        // Nasce fora do player, senão já começa colidindo com ele
+        let distance = (rectangle.width.max(rectangle.height) + PROJECTILE_SIZE) as f64;
+        let half = PROJECTILE_SIZE as f64 / 2.0;
+        commands.spawn((
+            BulletBundle {
+                marker: Bullet,
+                position: Position::new(
+                    center.x + direction.x() * distance - half,
+                    center.y + direction.y() * distance - half,
+                ),
+                velocity: Velocity::new(direction.x() * weapon.speed, direction.y() * weapon.speed),
+                body: PhysicsBody::BULLET,
+                rectangle: Rectangle::new(PROJECTILE_SIZE, PROJECTILE_SIZE),
+                solid: Solid::all(),
+                layers: CollisionLayers::new(
+                    Layer::Bullet.into(),
+                    Layers::ALL.without(Layer::Player),
+                ),
+                color: sdl2::pixels::Color::RED.into_fill(),
+                bounce: Bounce::new(true, 1.0),
+                explosive: Explosive(weapon.blast),
+            },
+            Projectile {
+                owner,
+                age: Duration::ZERO,
+                lifetime: weapon.lifetime_millis.map(Duration::from_millis),
+                max_bounces: weapon.max_bounces,
+                hits: 0,
+            },
+        ));
+    }
+}
+
+/// Explode o projétil mais antigo de cada player que apertou [Action::Detonate]
+pub fn detonate_projectiles(
+    players: Query<Entity, With<Player>>,
+    projectiles: Query<(
+        Entity,
+        &Projectile,
+        &Position,
+        &Rectangle,
+        Option<&Explosive>,
+    )>,
+    inputs: Res<InputState>,
+    mut explosions: EventWriter<Explosion>,
+    mut commands: Commands,
+) {
+    if !inputs.just_pressed(Action::Detonate) {
+        return;
+    }
+    for player in players.iter() {
+        let oldest = projectiles
+            .iter()
+            .filter(|(_, projectile, ..)| projectile.owner == player)
+            .max_by_key(|(_, projectile, ..)| projectile.age);
+        let Some((entity, _, position, rectangle, explosive)) = oldest else {
+            continue;
+        };
+        explosions.send(Explosion {
+            center: rectangle.on_position(position).center(),
+            blast: explosive.map(|e| e.0).unwrap_or_default(),
+            source: Some(entity),
+        });
+        commands.entity(entity).despawn();
+        debug!("Projectile {:?} exploded", entity);
+    }
+}
+
+/// Remove projéteis que passaram do tempo de vida ou do limite de quiques
+pub fn expire_projectiles(
+    mut projectiles: Query<(Entity, &mut Projectile)>,
+    statics: Query<(), (With<Solid>, Without<Velocity>)>,
+    mut collisions: EventReader<Collision>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for collision in collisions.read().filter(|c| c.started()) {
+        for entity in [collision.a, collision.b] {
+            let Some(other) = collision.other(entity) else {
+                continue;
+            };
+            if !statics.contains(other) {
+                continue;
+            }
+            if let Ok((_, mut projectile)) = projectiles.get_mut(entity) {
+                projectile.hits += 1;
+            }
+        }
+    }
+
+    for (entity, mut projectile) in projectiles.iter_mut() {
+        projectile.age += time.delta();
+        let expired = projectile.lifetime.is_some_and(|l| projectile.age >= l)
+            || projectile.max_bounces.is_some_and(|m| projectile.hits > m);
+        if expired {
+            commands.entity(entity).despawn();
+        }
+    }
+}
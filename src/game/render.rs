@@ -0,0 +1,151 @@
+use bevy_ecs::system::Resource;
+use sdl2::{
+    gfx::primitives::DrawRenderer,
+    pixels::Color,
+    rect::{Point, Rect},
+    render::{BlendMode, WindowCanvas},
+};
+
+/// Tamanho da área desenhada, em pixels
+#[derive(Debug, Clone, Copy, Resource)]
+pub struct Screen {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Screen {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+}
+
+/// Uma primitiva a ser desenhada, já em coordenadas da tela (y para baixo)
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCommand {
+    Rect {
+        rect: Rect,
+        color: Color,
+        fill: bool,
+    },
+    Polygon {
+        vx: Vec<i16>,
+        vy: Vec<i16>,
+        color: Color,
+        fill: bool,
+    },
+    Circle {
+        x: i16,
+        y: i16,
+        radius: i16,
+        color: Color,
+        fill: bool,
+    },
+    Line {
+        start: Point,
+        end: Point,
+        color: Color,
+    },
+    Text {
+        x: i16,
+        y: i16,
+        text: String,
+        color: Color,
+    },
+}
+
+/// O que os sistemas do [Render](super::draw::Render) desenharam no frame, na ordem em que
+/// desenharam. O [Renderer] consome tudo no fim do frame
+#[derive(Debug, Default, Resource)]
+pub struct DrawCommands(Vec<DrawCommand>);
+
+impl DrawCommands {
+    pub fn push(&mut self, command: DrawCommand) {
+        self.0.push(command);
+    }
+
+    pub fn take(&mut self) -> Vec<DrawCommand> {
+        std::mem::take(&mut self.0)
+    }
+}
+
+/// Onde os [DrawCommand]s viram pixels. O jogo não sabe se tem uma janela, então roda igual sem
+/// ela
+pub trait Renderer {
+    /// Desenha um frame inteiro, do fundo até os comandos na ordem
+    fn present(&mut self, commands: &[DrawCommand]);
+}
+
+/// Desenha na janela do SDL
+pub struct SdlRenderer {
+    canvas: WindowCanvas,
+    background: Color,
+}
+
+impl SdlRenderer {
+    pub fn new(mut canvas: WindowCanvas) -> Self {
+        canvas.set_blend_mode(BlendMode::Blend);
+        Self {
+            canvas,
+            background: Color::RGB(80, 80, 80),
+        }
+    }
+}
+
+impl Renderer for SdlRenderer {
+    fn present(&mut self, commands: &[DrawCommand]) {
+        let canvas = &mut self.canvas;
+        canvas.set_draw_color(self.background);
+        canvas.clear();
+        for command in commands {
+            let result = match command {
+                DrawCommand::Rect { rect, color, fill } => {
+                    canvas.set_draw_color(*color);
+                    if *fill {
+                        canvas.fill_rect(*rect)
+                    } else {
+                        canvas.draw_rect(*rect)
+                    }
+                }
+                DrawCommand::Polygon {
+                    vx,
+                    vy,
+                    color,
+                    fill,
+                } => {
+                    if *fill {
+                        canvas.filled_polygon(vx, vy, *color)
+                    } else {
+                        canvas.polygon(vx, vy, *color)
+                    }
+                }
+                DrawCommand::Circle {
+                    x,
+                    y,
+                    radius,
+                    color,
+                    fill,
+                } => {
+                    if *fill {
+                        canvas.filled_circle(*x, *y, *radius, *color)
+                    } else {
+                        canvas.circle(*x, *y, *radius, *color)
+                    }
+                }
+                DrawCommand::Line { start, end, color } => {
+                    canvas.set_draw_color(*color);
+                    canvas.draw_line(*start, *end)
+                }
+                DrawCommand::Text { x, y, text, color } => canvas.string(*x, *y, text, *color),
+            };
+            result.unwrap_or_else(|err| panic!("Can't draw {command:?}: {err}"));
+        }
+        canvas.present();
+    }
+}
+
+/// Não desenha nada, para rodar sem janela
+pub struct NullRenderer;
+
+impl Renderer for NullRenderer {
+    fn present(&mut self, _commands: &[DrawCommand]) {}
+}
@@ -0,0 +1,251 @@
+use super::{
+    camera::Camera,
+    editor::EditorKey,
+    input::{InputEvent, MouseLift, MousePosition, MousePress},
+};
+use bevy_ecs::{event::Events, world::World};
+use sdl2::keyboard::{Keycode, Mod};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    time::Duration,
+};
+
+/// Delta usado por padrão nas gravações, um frame a 60 fps
+pub const REPLAY_DELTA: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// Primeira linha do arquivo de replay
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Header {
+    /// Delta fixo do [Time](super::resources::Time) durante a gravação
+    delta_nanos: u64,
+}
+
+/// Tudo que chega de fora e muda o jogo. As posições do mouse são na janela, com o y para cima
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReplayEvent {
+    Input(InputEvent),
+    MousePress {
+        x: i32,
+        y: i32,
+    },
+    MouseLift {
+        x: i32,
+        y: i32,
+    },
+    MouseMotion {
+        x: i32,
+        y: i32,
+    },
+    /// Tecla para o editor, com o [Keycode] e o [Mod] do SDL como números
+    EditorKey {
+        key: i32,
+        keymod: u16,
+        repeat: bool,
+    },
+}
+
+impl ReplayEvent {
+    pub fn editor_key(key: Keycode, keymod: Mod, repeat: bool) -> Self {
+        Self::EditorKey {
+            key: key.into_i32(),
+            keymod: keymod.bits(),
+            repeat,
+        }
+    }
+
+    /// Manda o evento para o mundo. Os cliques viram posições do mundo com a câmera atual
+    pub fn send(self, world: &mut World) {
+        match self {
+            ReplayEvent::Input(event) => {
+                world.resource_mut::<Events<InputEvent>>().send(event);
+            }
+            ReplayEvent::MousePress { x, y } => {
+                let position = MousePosition::new(x, y).to_world(world.resource::<Camera>());
+                world
+                    .resource_mut::<Events<MousePress>>()
+                    .send(MousePress(position));
+            }
+            ReplayEvent::MouseLift { x, y } => {
+                let position = MousePosition::new(x, y).to_world(world.resource::<Camera>());
+                world
+                    .resource_mut::<Events<MouseLift>>()
+                    .send(MouseLift(position));
+            }
+            ReplayEvent::MouseMotion { x, y } => {
+                *world.resource_mut::<MousePosition>() = MousePosition::new(x, y);
+            }
+            ReplayEvent::EditorKey {
+                key,
+                keymod,
+                repeat,
+            } => {
+                let Some(key) = Keycode::from_i32(key) else {
+                    return;
+                };
+                world.resource_mut::<Events<EditorKey>>().send(EditorKey {
+                    key,
+                    keymod: Mod::from_bits_truncate(keymod),
+                    repeat,
+                });
+            }
+        }
+    }
+}
+
+/// Cada linha depois do [Header] é um [ReplayEvent] e o frame em que ele aconteceu
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct RecordedInput {
+    frame: u64,
+    #[serde(flatten)]
+    event: ReplayEvent,
+}
+
+fn invalid_data(err: serde_json::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+/// Grava os [ReplayEvent]s de cada frame num arquivo JSON lines
+pub struct Recorder<W: Write> {
+    writer: W,
+}
+
+impl Recorder<BufWriter<File>> {
+    pub fn create(path: impl AsRef<Path>, delta: Duration) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), delta)
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    pub fn new(mut writer: W, delta: Duration) -> io::Result<Self> {
+        let header = Header {
+            delta_nanos: delta.as_nanos() as u64,
+        };
+        serde_json::to_writer(&mut writer, &header).map_err(invalid_data)?;
+        writeln!(writer)?;
+        Ok(Self { writer })
+    }
+
+    /// Salva os eventos na hora, assim um crash não perde o que levou até ele
+    pub fn record(&mut self, frame: u64, events: &[ReplayEvent]) -> io::Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        for &event in events {
+            serde_json::to_writer(&mut self.writer, &RecordedInput { frame, event })
+                .map_err(invalid_data)?;
+            writeln!(self.writer)?;
+        }
+        self.writer.flush()
+    }
+}
+
+/// Eventos gravados por um [Recorder], entregues no mesmo frame em que aconteceram
+#[derive(Debug)]
+pub struct Replay {
+    delta: Duration,
+    inputs: VecDeque<RecordedInput>,
+}
+
+impl Replay {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    pub fn from_reader(reader: impl BufRead) -> io::Result<Self> {
+        let mut lines = reader.lines();
+        let header: Header = match lines.next() {
+            Some(line) => serde_json::from_str(&line?).map_err(invalid_data)?,
+            None => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Empty replay")),
+        };
+        let inputs = lines
+            .filter(|line| !line.as_ref().is_ok_and(|l| l.trim().is_empty()))
+            .map(|line| serde_json::from_str(&line?).map_err(invalid_data))
+            .collect::<io::Result<_>>()?;
+        Ok(Self {
+            delta: Duration::from_nanos(header.delta_nanos),
+            inputs,
+        })
+    }
+
+    /// Delta fixo que o [Time](super::resources::Time) precisa usar para reproduzir a gravação
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    /// Tira da fila os eventos de `frame`. Os frames precisam ser pedidos em ordem
+    pub fn events(&mut self, frame: u64) -> Vec<ReplayEvent> {
+        let mut events = Vec::new();
+        while let Some(input) = self.inputs.front().filter(|i| i.frame <= frame) {
+            events.push(input.event);
+            self.inputs.pop_front();
+        }
+        events
+    }
+
+    pub fn finished(&self) -> bool {
+        self.inputs.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        components::Position,
+        input::{Action, ActionState},
+        render::Screen,
+    };
+
+    #[test]
+    fn replays_events_on_recorded_frames() {
+        let jump = ReplayEvent::Input(InputEvent::new(Action::Jump, ActionState::Active));
+        let left = ReplayEvent::Input(InputEvent::analog(Action::Left, 0.5));
+        let click = ReplayEvent::MousePress { x: 10, y: 20 };
+        let undo = ReplayEvent::editor_key(Keycode::Z, Mod::LCTRLMOD, false);
+        let mut file = Vec::new();
+        let mut recorder = Recorder::new(&mut file, REPLAY_DELTA).unwrap();
+        recorder.record(0, &[]).unwrap();
+        recorder.record(2, &[jump, left]).unwrap();
+        recorder.record(3, &[click, undo]).unwrap();
+        recorder.record(5, &[jump]).unwrap();
+
+        let mut replay = Replay::from_reader(file.as_slice()).unwrap();
+        assert_eq!(replay.delta(), REPLAY_DELTA);
+        assert_eq!(replay.events(0), vec![]);
+        assert_eq!(replay.events(1), vec![]);
+        assert_eq!(replay.events(2), vec![jump, left]);
+        assert_eq!(replay.events(3), vec![click, undo]);
+        assert!(!replay.finished());
+        assert_eq!(replay.events(5), vec![jump]);
+        assert!(replay.finished());
+    }
+
+    #[test]
+    fn sends_editor_keys_and_clicks() {
+        let mut world = World::new();
+        world.insert_resource(Screen::new(800, 600));
+        world.init_resource::<Camera>();
+        world.init_resource::<Events<EditorKey>>();
+        world.init_resource::<Events<MousePress>>();
+        world.init_resource::<MousePosition>();
+
+        ReplayEvent::editor_key(Keycode::Z, Mod::LCTRLMOD, false).send(&mut world);
+        ReplayEvent::MousePress { x: 10, y: 20 }.send(&mut world);
+        ReplayEvent::MouseMotion { x: 30, y: 40 }.send(&mut world);
+
+        let keys = world.resource::<Events<EditorKey>>();
+        let key = keys.iter_current_update_events().next().unwrap();
+        assert_eq!((key.key, key.keymod), (Keycode::Z, Mod::LCTRLMOD));
+        let camera = world.resource::<Camera>().pos.clone();
+        let presses = world.resource::<Events<MousePress>>();
+        let press = presses.iter_current_update_events().next().unwrap();
+        assert_eq!(press.0, Position::new(camera.x + 10.0, camera.y + 20.0));
+        let mouse = world.resource::<MousePosition>();
+        assert_eq!((mouse.x, mouse.y), (30, 40));
+    }
+}
@@ -11,6 +11,8 @@ pub struct Time {
     last_update: Instant,
     delta: Duration,
     elapsed: Duration,
+    /// Quando definido, todo frame anda exatamente esse tempo, independente do relógio
+    fixed_delta: Option<Duration>,
 }
 
 impl Time {
@@ -19,6 +21,16 @@ impl Time {
             last_update: Instant::now(),
             delta: Duration::ZERO,
             elapsed: Duration::ZERO,
+            fixed_delta: None,
+        }
+    }
+
+    /// Tempo que anda sempre `delta` por frame, assim a simulação não depende da velocidade da
+    /// máquina. Usado para gravar e reproduzir replays
+    pub fn fixed(delta: Duration) -> Self {
+        Self {
+            fixed_delta: Some(delta),
+            ..Self::new()
         }
     }
 
@@ -32,7 +44,9 @@ impl Time {
 
     pub fn update(&mut self) {
         let now = Instant::now();
-        self.delta = cmp::min(now - self.last_update, Self::MAX_DELTA);
+        self.delta = self
+            .fixed_delta
+            .unwrap_or_else(|| cmp::min(now - self.last_update, Self::MAX_DELTA));
         self.elapsed += self.delta;
         self.last_update = now;
     }
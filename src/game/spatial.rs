@@ -0,0 +1,345 @@
+use super::components::{
+    CollisionLayers, Normal, Position, Rectangle, Shape, Side, Solid, Velocity,
+};
+use bevy_ecs::{
+    entity::Entity,
+    query::{Has, With},
+    system::{Query, SystemParam},
+};
+
+type Point = (f64, f64);
+
+/// Resultado de um [SpatialQuery::cast_ray] ou [SpatialQuery::cast_rect]
+#[derive(Debug, Clone)]
+pub struct RayHit {
+    pub entity: Entity,
+    /// No raycast é onde o raio tocou a superfície. No shape cast é a [Position] que o retângulo
+    /// teria no momento do impacto
+    pub point: Position,
+    pub distance: f64,
+    /// Normal da superfície atingida, apontando para quem fez o cast
+    pub normal: Normal,
+}
+
+/// Quais entidades um cast pode atingir
+#[derive(Debug, Clone, Default)]
+pub struct CastFilter {
+    /// Camadas de quem está fazendo o cast, comparadas com as de cada entidade como numa colisão
+    pub layers: Option<CollisionLayers>,
+    pub exclude: Option<Entity>,
+    /// Ignora entidades com [Velocity]
+    pub only_static: bool,
+}
+
+impl CastFilter {
+    pub fn excluding(entity: Entity) -> Self {
+        Self {
+            exclude: Some(entity),
+            ..Default::default()
+        }
+    }
+}
+
+type Solids<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Position,
+        &'static Rectangle,
+        Option<&'static Shape>,
+        Option<&'static CollisionLayers>,
+        Has<Velocity>,
+    ),
+    With<Solid>,
+>;
+
+/// Consultas de raycast e shape cast contra todas as hitboxes [Solid] do mundo
+#[derive(SystemParam)]
+pub struct SpatialQuery<'w, 's> {
+    solids: Solids<'w, 's>,
+}
+
+impl SpatialQuery<'_, '_> {
+    /// Lança um raio de `origin` na direção `direction` e retorna a primeira hitbox atingida
+    pub fn cast_ray(
+        &self,
+        origin: &Position,
+        direction: Normal,
+        max_distance: f64,
+        filter: &CastFilter,
+    ) -> Option<RayHit> {
+        let origin = (origin.x, origin.y);
+        let direction = (direction.x(), direction.y());
+        self.cast(filter, |pos, rect, shape| {
+            let (left, bottom) = (pos.x, pos.y);
+            let (right, top) = (left + rect.width as f64, bottom + rect.height as f64);
+            match shape {
+                None => ray_polygon(
+                    origin,
+                    direction,
+                    max_distance,
+                    &[(left, bottom), (right, bottom), (right, top), (left, top)],
+                ),
+                Some(Shape::Slope { high }) => ray_polygon(
+                    origin,
+                    direction,
+                    max_distance,
+                    &slope_vertices(*high, left, right, bottom, top),
+                ),
+                Some(Shape::Circle) => {
+                    let radius = rect.width.min(rect.height) as f64 / 2.0;
+                    let center = ((left + right) / 2.0, (bottom + top) / 2.0);
+                    ray_circle(origin, direction, max_distance, center, radius)
+                }
+            }
+            .map(|(distance, normal)| {
+                let point = Position::new(
+                    origin.0 + direction.0 * distance,
+                    origin.1 + direction.1 * distance,
+                );
+                (point, distance, normal)
+            })
+        })
+    }
+
+    /// Move um retângulo que está em `origin` na direção `direction` e retorna a primeira hitbox
+    /// que ele tocaria
+    pub fn cast_rect(
+        &self,
+        rectangle: &Rectangle,
+        origin: &Position,
+        direction: Normal,
+        max_distance: f64,
+        filter: &CastFilter,
+    ) -> Option<RayHit> {
+        let (w, h) = (rectangle.width as f64, rectangle.height as f64);
+        let origin = (origin.x, origin.y);
+        let direction = (direction.x(), direction.y());
+        self.cast(filter, |pos, rect, shape| {
+            // O retângulo toca a hitbox quando a sua posição está dentro da soma de Minkowski
+            // da hitbox com o retângulo invertido, então basta lançar um raio contra ela
+            let (left, bottom) = (pos.x - w, pos.y - h);
+            let (right, top) = (pos.x + rect.width as f64, pos.y + rect.height as f64);
+            match shape {
+                None => ray_polygon(
+                    origin,
+                    direction,
+                    max_distance,
+                    &[(left, bottom), (right, bottom), (right, top), (left, top)],
+                ),
+                Some(Shape::Slope { high }) => {
+                    let triangle = slope_vertices(*high, pos.x, right, pos.y, top);
+                    let sums: Vec<Point> = triangle
+                        .iter()
+                        .flat_map(|&(x, y)| [(x - w, y - h), (x, y - h), (x, y), (x - w, y)])
+                        .collect();
+                    ray_polygon(origin, direction, max_distance, &convex_hull(sums))
+                }
+                Some(Shape::Circle) => {
+                    let radius = rect.width.min(rect.height) as f64 / 2.0;
+                    let (cx, cy) = (
+                        pos.x + rect.width as f64 / 2.0,
+                        pos.y + rect.height as f64 / 2.0,
+                    );
+                    // Retângulo com cantos arredondados: duas faixas e um círculo em cada canto
+                    let horizontal = [
+                        (cx - w - radius, cy - h),
+                        (cx + radius, cy - h),
+                        (cx + radius, cy),
+                        (cx - w - radius, cy),
+                    ];
+                    let vertical = [
+                        (cx - w, cy - h - radius),
+                        (cx, cy - h - radius),
+                        (cx, cy + radius),
+                        (cx - w, cy + radius),
+                    ];
+                    let corners = [(cx - w, cy - h), (cx, cy - h), (cx, cy), (cx - w, cy)];
+                    [
+                        ray_polygon(origin, direction, max_distance, &horizontal),
+                        ray_polygon(origin, direction, max_distance, &vertical),
+                    ]
+                    .into_iter()
+                    .chain(
+                        corners
+                            .iter()
+                            .map(|&c| ray_circle(origin, direction, max_distance, c, radius)),
+                    )
+                    .flatten()
+                    .min_by(|a, b| a.0.total_cmp(&b.0))
+                }
+            }
+            .map(|(distance, normal)| {
+                let point = Position::new(
+                    origin.0 + direction.0 * distance,
+                    origin.1 + direction.1 * distance,
+                );
+                (point, distance, normal)
+            })
+        })
+    }
+
+    fn cast<F>(&self, filter: &CastFilter, intersect: F) -> Option<RayHit>
+    where
+        F: Fn(&Position, &Rectangle, Option<&Shape>) -> Option<(Position, f64, Normal)>,
+    {
+        self.solids
+            .iter()
+            .filter(|(entity, _, _, _, layers, moving)| {
+                filter.exclude != Some(*entity)
+                    && !(filter.only_static && *moving)
+                    && CollisionLayers::interacts(filter.layers.as_ref(), *layers)
+            })
+            .filter_map(|(entity, pos, rect, shape, _, _)| {
+                intersect(pos, rect, shape).map(|(point, distance, normal)| RayHit {
+                    entity,
+                    point,
+                    distance,
+                    normal,
+                })
+            })
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+}
+
+/// Vértices da rampa em sentido anti-horário
+fn slope_vertices(high: Side, left: f64, right: f64, bottom: f64, top: f64) -> [Point; 3] {
+    match high {
+        Side::Right => [(left, bottom), (right, bottom), (right, top)],
+        Side::Left => [(left, bottom), (right, bottom), (left, top)],
+    }
+}
+
+/// Fecho convexo em sentido anti-horário (monotone chain)
+fn convex_hull(mut points: Vec<Point>) -> Vec<Point> {
+    points.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    let cross =
+        |o: Point, a: Point, b: Point| (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0);
+    let mut hull: Vec<Point> = Vec::with_capacity(points.len() * 2);
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let start = hull.len();
+        for p in pass {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+/// Intersecção de um raio com um polígono convexo em sentido anti-horário
+fn ray_polygon(
+    origin: Point,
+    direction: Point,
+    max_distance: f64,
+    vertices: &[Point],
+) -> Option<(f64, Normal)> {
+    let edges = || {
+        vertices
+            .iter()
+            .zip(vertices.iter().cycle().skip(1))
+            .map(|(&a, &b)| (a, b))
+    };
+
+    let inside =
+        edges().all(|(a, b)| (b.0 - a.0) * (origin.1 - a.1) - (b.1 - a.1) * (origin.0 - a.0) > 0.0);
+    if inside {
+        return Some((0.0, Normal::new(-direction.0, -direction.1)));
+    }
+
+    edges()
+        .filter_map(|(a, b)| {
+            let edge = (b.0 - a.0, b.1 - a.1);
+            let normal = (edge.1, -edge.0);
+            let facing = normal.0 * direction.0 + normal.1 * direction.1;
+            if facing >= 0.0 {
+                return None;
+            }
+            let denominator = direction.0 * edge.1 - direction.1 * edge.0;
+            let offset = (a.0 - origin.0, a.1 - origin.1);
+            let t = (offset.0 * edge.1 - offset.1 * edge.0) / denominator;
+            let u = (offset.0 * direction.1 - offset.1 * direction.0) / denominator;
+            ((0.0..=max_distance).contains(&t) && (0.0..=1.0).contains(&u))
+                .then(|| (t, Normal::new(normal.0, normal.1)))
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+}
+
+fn ray_circle(
+    origin: Point,
+    direction: Point,
+    max_distance: f64,
+    center: Point,
+    radius: f64,
+) -> Option<(f64, Normal)> {
+    let offset = (origin.0 - center.0, origin.1 - center.1);
+    let c = offset.0.powi(2) + offset.1.powi(2) - radius.powi(2);
+    if c < 0.0 {
+        return Some((0.0, Normal::new(-direction.0, -direction.1)));
+    }
+    let b = offset.0 * direction.0 + offset.1 * direction.1;
+    let discriminant = b.powi(2) - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let t = -b - discriminant.sqrt();
+    if !(0.0..=max_distance).contains(&t) {
+        return None;
+    }
+    let hit = (origin.0 + direction.0 * t, origin.1 + direction.1 * t);
+    Some((t, Normal::new(hit.0 - center.0, hit.1 - center.1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SQUARE: [Point; 4] = [(10.0, 0.0), (20.0, 0.0), (20.0, 10.0), (10.0, 10.0)];
+
+    #[test]
+    fn ray_hits_closest_polygon_edge() {
+        let (t, normal) = ray_polygon((0.0, 5.0), (1.0, 0.0), 100.0, &SQUARE).unwrap();
+        assert_eq!(t, 10.0);
+        assert_eq!((normal.x(), normal.y()), (-1.0, 0.0));
+
+        assert!(ray_polygon((0.0, 5.0), (1.0, 0.0), 5.0, &SQUARE).is_none());
+        assert!(ray_polygon((0.0, 15.0), (1.0, 0.0), 100.0, &SQUARE).is_none());
+    }
+
+    #[test]
+    fn ray_starting_inside_hits_immediately() {
+        let (t, _) = ray_polygon((15.0, 5.0), (0.0, 1.0), 100.0, &SQUARE).unwrap();
+        assert_eq!(t, 0.0);
+    }
+
+    #[test]
+    fn ray_hits_circle_surface() {
+        let (t, normal) = ray_circle((0.0, 0.0), (0.0, -1.0), 100.0, (0.0, -20.0), 5.0).unwrap();
+        assert_eq!(t, 15.0);
+        assert_eq!((normal.x(), normal.y()), (0.0, 1.0));
+    }
+
+    #[test]
+    fn hull_of_slope_sum_is_convex() {
+        let triangle = slope_vertices(Side::Right, 0.0, 10.0, 0.0, 10.0);
+        let sums = triangle
+            .iter()
+            .flat_map(|&(x, y)| [(x - 2.0, y - 2.0), (x, y - 2.0), (x, y), (x - 2.0, y)])
+            .collect();
+        let hull = convex_hull(sums);
+        assert_eq!(hull.len(), 5);
+        let (t, normal) = ray_polygon((5.0, 20.0), (0.0, -1.0), 100.0, &hull).unwrap();
+        assert!(t > 10.0 && t < 20.0);
+        assert!(normal.x() < 0.0 && normal.y() > 0.0);
+    }
+}
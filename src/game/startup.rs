@@ -1,33 +1,138 @@
-use super::map::Entity;
-use crate::game::resources::Spawn;
+use super::{
+    map::{Entity, MapEntity, MapSource},
+    projectile::Projectile,
+};
+use crate::game::{camera::Camera, components::Position, resources::Spawn};
 use bevy_ecs::{
+    entity::Entity as EcsEntity,
+    event::EventReader,
+    query::{Or, With},
     schedule::ScheduleLabel,
-    system::{Commands, ResMut},
+    system::{Commands, Query, ResMut, Resource},
 };
-use std::fs;
+use log::error;
+use std::{collections::BTreeMap, fs};
+
+pub const FIRST_MAP: &str = "assets/maps/map_01.json";
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, ScheduleLabel)]
 pub struct Startup;
 
-pub fn init_map_system(mut commands: Commands, mut spawn: ResMut<Spawn>) {
-    let map_file = fs::read_to_string("assets/maps/map_01.json").expect("Map file not found");
-    let entities = serde_json::from_str::<Vec<Entity>>(&map_file).expect("Failed to parse map");
+/// Pede a troca do mapa atual pelo do arquivo em `path`
+#[derive(Debug, Clone, bevy_ecs::event::Event)]
+pub struct LoadMap {
+    pub path: String,
+}
+
+/// Arquivo do mapa carregado agora, onde o editor salva
+#[derive(Debug, Resource, Clone)]
+pub struct CurrentMap(pub String);
+
+impl Default for CurrentMap {
+    fn default() -> Self {
+        Self(FIRST_MAP.to_string())
+    }
+}
+
+/// O [MapSource] de todas as entidades do mapa, inclusive as que o jogo já removeu, como moedas
+/// pegas. É o que o editor salva, assim jogar antes de salvar não apaga nada do arquivo
+#[derive(Debug, Default, Resource)]
+pub struct MapSources(pub BTreeMap<EcsEntity, serde_json::Value>);
+
+/// O que é removido ao trocar de mapa. Projéteis não vêm do mapa, mas não fazem sentido no próximo
+type MapScoped = Or<(With<MapEntity>, With<Projectile>)>;
+
+pub fn init_map_system(
+    mut commands: Commands,
+    mut spawn: ResMut<Spawn>,
+    mut current: ResMut<CurrentMap>,
+    mut sources: ResMut<MapSources>,
+) {
+    let map = read_map(FIRST_MAP).unwrap_or_else(|err| panic!("{err}"));
+    spawn_map(map, &mut commands, &mut spawn, &mut sources);
+    current.0 = FIRST_MAP.to_string();
+}
+
+/// Troca de mapa no meio do jogo. Um mapa inválido não derruba o jogo, o atual continua
+pub fn load_map_system(
+    mut load_map_events: EventReader<LoadMap>,
+    map_entities: Query<EcsEntity, MapScoped>,
+    mut commands: Commands,
+    mut spawn: ResMut<Spawn>,
+    mut camera: ResMut<Camera>,
+    mut current: ResMut<CurrentMap>,
+    mut sources: ResMut<MapSources>,
+) {
+    // Se pedirem mais de um mapa no mesmo frame só o último importa
+    let Some(LoadMap { path }) = load_map_events.read().last() else {
+        return;
+    };
+    let map = match read_map(path) {
+        Ok(map) => map,
+        Err(err) => {
+            error!("{err}, keeping the current map");
+            return;
+        }
+    };
+    for entity in map_entities.iter() {
+        commands.entity(entity).despawn();
+    }
+    spawn_map(map, &mut commands, &mut spawn, &mut sources);
+    camera.pos = Position::new(0, 0);
+    current.0 = path.clone();
+}
+
+/// Lê e valida o mapa em `path`, junto com o JSON de cada entidade
+fn read_map(path: &str) -> Result<Vec<(Entity, serde_json::Value)>, String> {
+    let map_file =
+        fs::read_to_string(path).map_err(|err| format!("Failed to read map {path}: {err}"))?;
+    let sources = serde_json::from_str::<Vec<serde_json::Value>>(&map_file)
+        .map_err(|err| format!("Failed to parse map {path}: {err}"))?;
+    let entities = sources
+        .iter()
+        .cloned()
+        .map(serde_json::from_value::<Entity>)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("Failed to parse map {path}: {err}"))?;
     match entities
         .iter()
         .fold(0, |acc, e| acc + matches!(e, Entity::Player { .. }) as u32)
     {
-        0 => panic!("Map defined no player"),
-        2.. => panic!("Map defined more than one player"),
+        0 => return Err(format!("Map {path} defined no player")),
+        2.. => return Err(format!("Map {path} defined more than one player")),
         _ => (),
     }
+    Ok(entities.into_iter().zip(sources).collect())
+}
 
-    if let Some(Entity::Player { position }) =
-        entities.iter().find(|e| matches!(e, Entity::Player { .. }))
+fn spawn_map(
+    map: Vec<(Entity, serde_json::Value)>,
+    commands: &mut Commands,
+    spawn: &mut Spawn,
+    sources: &mut MapSources,
+) {
+    if let Some((Entity::Player { position, .. }, _)) =
+        map.iter().find(|(e, _)| matches!(e, Entity::Player { .. }))
     {
         spawn.0 = position.clone();
     }
 
-    for entity in entities {
-        entity.spawn(&mut commands);
+    sources.0.clear();
+    for (entity, source) in map {
+        let id = entity.spawn(commands);
+        sources.0.insert(id, source.clone());
+        commands.entity(id).insert(MapSource(source));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_maps_are_errors() {
+        assert!(read_map(FIRST_MAP).is_ok());
+        assert!(read_map("assets/maps/missing.json").is_err());
+        assert!(read_map("assets/bindings.example.json").is_err());
     }
 }
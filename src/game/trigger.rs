@@ -0,0 +1,211 @@
+use super::{
+    camera::Camera,
+    components::{Gravitable, Health, Player, Position, Velocity},
+    physics::Collision,
+    player::respawn,
+    resources::Spawn,
+    startup::LoadMap,
+};
+use bevy_ecs::{
+    entity::Entity,
+    event::{EventReader, EventWriter},
+    prelude::Component,
+    query::{With, Without},
+    system::{Commands, Local, Query, ResMut, SystemParam},
+};
+use log::debug;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TriggerPhase {
+    /// No frame em que a entidade entra na área
+    #[default]
+    Enter,
+    /// Em todo frame depois de entrar, enquanto a entidade estiver na área
+    Stay,
+    /// No frame em que a entidade sai da área
+    Exit,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TriggerAction {
+    /// Mata o player, que volta para o [Spawn], ou remove qualquer outra entidade
+    Kill,
+    Heal {
+        amount: u32,
+    },
+    /// Muda a [Gravitable::base_scale], que vale até outro trigger mudar de novo
+    SetGravity {
+        scale: f64,
+    },
+    /// Muda o [Spawn] para `position`, ou para a posição do próprio trigger
+    SetSpawn {
+        #[serde(default)]
+        position: Option<Position>,
+    },
+    LoadMap {
+        path: String,
+    },
+    /// Só emite um [CustomTrigger] com esse nome
+    Custom {
+        name: String,
+    },
+}
+
+/// Área sem colisão que executa uma ação nas entidades que passam por ela
+#[derive(Debug, Component)]
+pub struct Trigger {
+    pub action: TriggerAction,
+    pub on: TriggerPhase,
+}
+
+impl Trigger {
+    pub fn new(action: TriggerAction, on: TriggerPhase) -> Self {
+        Self { action, on }
+    }
+}
+
+/// Emitido em todas as fases de toda entidade que está dentro de um [Trigger]
+#[derive(Debug, Clone, Copy, bevy_ecs::event::Event)]
+pub struct TriggerEvent {
+    pub trigger: Entity,
+    pub entity: Entity,
+    pub phase: TriggerPhase,
+}
+
+#[derive(Debug, Clone, bevy_ecs::event::Event)]
+pub struct CustomTrigger {
+    pub name: String,
+    pub trigger: Entity,
+    pub entity: Entity,
+}
+
+/// Mostra no log os [CustomTrigger]s, para quem está montando o mapa ver quando eles disparam
+pub fn log_custom_triggers(mut custom: EventReader<CustomTrigger>) {
+    for CustomTrigger {
+        name,
+        trigger,
+        entity,
+    } in custom.read()
+    {
+        debug!("Custom trigger {name} fired by {entity:?} on {trigger:?}");
+    }
+}
+
+/// Transforma as [Collision]s com triggers em [TriggerEvent]s
+pub fn detect_triggers(
+    mut collisions: EventReader<Collision>,
+    triggers: Query<(), With<Trigger>>,
+    mut inside: Local<BTreeSet<(Entity, Entity)>>,
+    mut trigger_events: EventWriter<TriggerEvent>,
+) {
+    let mut entered = BTreeSet::new();
+    for collision in collisions.read() {
+        let (trigger, entity) = if triggers.contains(collision.b) {
+            (collision.b, collision.a)
+        } else if triggers.contains(collision.a) {
+            (collision.a, collision.b)
+        } else {
+            continue;
+        };
+
+        let phase = if collision.started() {
+            inside.insert((trigger, entity));
+            entered.insert((trigger, entity));
+            TriggerPhase::Enter
+        } else {
+            inside.remove(&(trigger, entity));
+            TriggerPhase::Exit
+        };
+        trigger_events.send(TriggerEvent {
+            trigger,
+            entity,
+            phase,
+        });
+    }
+
+    for &(trigger, entity) in inside.difference(&entered) {
+        trigger_events.send(TriggerEvent {
+            trigger,
+            entity,
+            phase: TriggerPhase::Stay,
+        });
+    }
+}
+
+type PlayerNotTrigger = (With<Player>, Without<Trigger>);
+
+/// O que as ações dos triggers alteram no mundo
+#[derive(SystemParam)]
+pub struct TriggerTargets<'w, 's> {
+    players: Query<'w, 's, (&'static mut Position, &'static mut Velocity), PlayerNotTrigger>,
+    healths: Query<'w, 's, &'static mut Health>,
+    gravitables: Query<'w, 's, &'static mut Gravitable>,
+    spawn: ResMut<'w, Spawn>,
+    camera: ResMut<'w, Camera>,
+    commands: Commands<'w, 's>,
+}
+
+pub fn apply_trigger_actions(
+    mut trigger_events: EventReader<TriggerEvent>,
+    triggers: Query<(&Trigger, Option<&Position>)>,
+    mut targets: TriggerTargets,
+    mut load_map: EventWriter<LoadMap>,
+    mut custom: EventWriter<CustomTrigger>,
+) {
+    let TriggerTargets {
+        players,
+        healths,
+        gravitables,
+        spawn,
+        camera,
+        commands,
+    } = &mut targets;
+    for event in trigger_events.read() {
+        let Ok((trigger, trigger_position)) = triggers.get(event.trigger) else {
+            continue;
+        };
+        if trigger.on != event.phase {
+            continue;
+        }
+
+        match &trigger.action {
+            TriggerAction::Kill => {
+                if let Ok((mut position, mut velocity)) = players.get_mut(event.entity) {
+                    debug!("Player killed by trigger {:?}", event.trigger);
+                    respawn(&mut position, &mut velocity, spawn, camera);
+                } else if let Some(mut entity) = commands.get_entity(event.entity) {
+                    entity.despawn();
+                }
+            }
+            TriggerAction::Heal { amount } => {
+                if let Ok(mut health) = healths.get_mut(event.entity) {
+                    health.heal(*amount);
+                }
+            }
+            TriggerAction::SetGravity { scale } => {
+                if let Ok(mut gravitable) = gravitables.get_mut(event.entity) {
+                    gravitable.base_scale = *scale;
+                }
+            }
+            TriggerAction::SetSpawn { position } => {
+                if let Some(position) = position.as_ref().or(trigger_position) {
+                    spawn.0 = position.clone();
+                }
+            }
+            TriggerAction::LoadMap { path } => {
+                load_map.send(LoadMap { path: path.clone() });
+            }
+            TriggerAction::Custom { name } => {
+                custom.send(CustomTrigger {
+                    name: name.clone(),
+                    trigger: event.trigger,
+                    entity: event.entity,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use super::{
+    components::{Gravitable, Player, Velocity},
+    input::{Action, InputState},
+    physics::Collision,
+};
+use bevy_ecs::{
+    change_detection::Res,
+    entity::Entity,
+    event::EventReader,
+    prelude::{Component, Query},
+    query::With,
+    system::{Commands, Local},
+};
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Water {
+    pub gravity_scale: f64,
+    pub max_fall_speed: f64,
+    /// Velocidade para cima de cada braçada com [Action::Up]
+    pub stroke: f64,
+}
+
+impl Default for Water {
+    fn default() -> Self {
+        Self {
+            gravity_scale: 0.3,
+            max_fall_speed: 150.0,
+            stroke: 400.0,
+        }
+    }
+}
+
+/// Área sem colisão que muda a física de quem está dentro dela
+#[derive(Debug, Clone, Copy, Component, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Volume {
+    Water(Water),
+    /// Multiplica a gravidade, `scale` negativo inverte a direção
+    Gravity {
+        scale: f64,
+    },
+}
+
+/// Marca quem está dentro de uma [Volume::Water]
+#[derive(Debug, Component)]
+pub struct Swimming(pub Water);
+
+/// Junta o efeito de todos os volumes em que uma entidade está. As escalas se multiplicam com a
+/// `base_scale` e vale a menor velocidade de queda
+fn combine<'a>(
+    base_scale: f64,
+    volumes: impl Iterator<Item = &'a Volume>,
+) -> (Gravitable, Option<Water>) {
+    let mut gravitable = Gravitable {
+        scale: base_scale,
+        base_scale,
+        max_fall_speed: None,
+    };
+    let mut water = None;
+    for volume in volumes {
+        match volume {
+            Volume::Water(w) => {
+                gravitable.scale *= w.gravity_scale;
+                gravitable.max_fall_speed = Some(
+                    gravitable
+                        .max_fall_speed
+                        .map_or(w.max_fall_speed, |max| max.min(w.max_fall_speed)),
+                );
+                water.get_or_insert(*w);
+            }
+            Volume::Gravity { scale } => gravitable.scale *= scale,
+        }
+    }
+    (gravitable, water)
+}
+
+/// Acompanha quem está em cada [Volume] e atualiza o [Gravitable] dessas entidades
+pub fn apply_volumes(
+    mut collisions: EventReader<Collision>,
+    volumes: Query<&Volume>,
+    mut gravitables: Query<(Entity, &mut Gravitable, Option<&Swimming>)>,
+    mut inside: Local<HashMap<Entity, BTreeSet<Entity>>>,
+    mut commands: Commands,
+) {
+    for collision in collisions.read() {
+        let (volume, entity) = if volumes.contains(collision.b) {
+            (collision.b, collision.a)
+        } else if volumes.contains(collision.a) {
+            (collision.a, collision.b)
+        } else {
+            continue;
+        };
+        let entered = inside.entry(entity).or_default();
+        if collision.started() {
+            entered.insert(volume);
+        } else {
+            entered.remove(&volume);
+        }
+    }
+
+    // Esquece quem foi removido e os volumes que não existem mais
+    inside.retain(|entity, entered| {
+        entered.retain(|volume| volumes.contains(*volume));
+        !entered.is_empty() && gravitables.contains(*entity)
+    });
+
+    for (entity, mut gravitable, swimming) in gravitables.iter_mut() {
+        let in_volumes = inside
+            .get(&entity)
+            .into_iter()
+            .flatten()
+            .filter_map(|volume| volumes.get(*volume).ok());
+        let (updated, water) = combine(gravitable.base_scale, in_volumes);
+        if *gravitable != updated {
+            *gravitable = updated;
+        }
+        match (water, swimming) {
+            (Some(water), Some(Swimming(current))) if water == *current => (),
+            (Some(water), _) => {
+                commands.entity(entity).insert(Swimming(water));
+            }
+            (None, Some(_)) => {
+                commands.entity(entity).remove::<Swimming>();
+            }
+            (None, None) => (),
+        }
+    }
+}
+
+/// Dá uma braçada para cima a cada vez que [Action::Up] é apertado na água
+pub fn swim(mut query: Query<(&mut Velocity, &Swimming), With<Player>>, inputs: Res<InputState>) {
+    if !inputs.just_pressed(Action::Up) {
+        return;
+    }
+    for (mut velocity, Swimming(water)) in query.iter_mut() {
+        velocity.y = velocity.y.max(water.stroke);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn volumes_combine() {
+        let volumes = [
+            Volume::Gravity { scale: -0.5 },
+            Volume::Water(Water {
+                max_fall_speed: 100.0,
+                ..Default::default()
+            }),
+            Volume::Water(Water::default()),
+        ];
+        let (gravitable, water) = combine(2.0, volumes.iter());
+        assert!((gravitable.scale - 2.0 * -0.5 * 0.3 * 0.3).abs() < 1e-9);
+        assert_eq!(gravitable.base_scale, 2.0);
+        assert_eq!(gravitable.max_fall_speed, Some(100.0));
+        assert_eq!(water.map(|w| w.max_fall_speed), Some(100.0));
+        assert_eq!(combine(1.0, [].iter()), (Gravitable::default(), None));
+    }
+}
@@ -2,30 +2,52 @@ mod game;
 
 use crate::game::{
     camera::{move_camera, Camera},
-    player::player_enter_kill_zone,
     resources::{Spawn, Time},
 };
 use bevy_ecs::{
-    event::Events,
+    event::{event_update_system, EventRegistry},
     prelude::{Schedule, *},
     world::World,
 };
 use dotenv::dotenv;
 use game::{
-    draw::{draw, Render},
+    abilities::use_abilities,
+    bindings::{KeyBindings, DEFAULT_BINDINGS_PATH},
+    draw::{draw, draw_ropes, Render},
+    editor::{
+        apply_editor_tool, draw_editor, handle_editor_keys, Editor, EditorKey, EditorUpdate,
+        DEFAULT_GRID,
+    },
+    explosion::{apply_explosions, Explosion},
+    gamepad::Gamepads,
+    grapple::{apply_rope, fire_grapple},
+    history::History,
     input::{
-        handle_mouse, insert_mouse_resources, insert_mouse_square, update_input_state, InputEvent,
-        InputState, MouseLift, MousePress,
+        handle_mouse, insert_mouse_resources, update_cursor, update_input_state, InputEvent,
+        InputState,
     },
+    ladder::{climb, track_ladders},
     physics::{
-        gravitate, handle_bounce_moving_static, handle_collision_moving_static, limit_velocity,
-        move_system,
+        apply_drag, apply_friction, clear_contacts, detect_collisions, gravitate,
+        handle_bounce_moving_static, handle_collision_moving_moving,
+        handle_collision_moving_shapes, handle_collision_moving_static, limit_velocity,
+        move_system, Collision,
+    },
+    player::{
+        crouch, handle_player_input, player_collides_coin, update_jump_time, wall_slide_and_jump,
+    },
+    projectile::{detonate_projectiles, expire_projectiles, fire_projectiles},
+    render::{DrawCommands, NullRenderer, Renderer, Screen, SdlRenderer},
+    replay::{Recorder, Replay, ReplayEvent, REPLAY_DELTA},
+    startup::{init_map_system, load_map_system, CurrentMap, LoadMap, MapSources, Startup},
+    trigger::{
+        apply_trigger_actions, detect_triggers, log_custom_triggers, CustomTrigger, TriggerEvent,
     },
-    player::{handle_player_input, player_attack, player_collides_coin, update_jump_time},
-    startup::{init_map_system, Startup},
+    volume::{apply_volumes, swim},
     Update,
 };
-use sdl2::{event::Event, keyboard::Keycode, pixels::Color, render::WindowCanvas};
+use log::{error, info};
+use sdl2::{event::Event, keyboard::Keycode};
 use simple_logger::SimpleLogger;
 use std::time::Duration;
 
@@ -45,72 +67,66 @@ fn main() -> Result<(), String> {
 
     SimpleLogger::new().init().expect("log init failed");
 
+    // Sem janela o jogo roda sem desenhar nada e sem esperar entre os frames, para CI e replays
+    let headless = dotenv::var("HEADLESS").is_ok_and(|v| v != "0");
     let sdl_context = sdl2::init().expect("Could not init SDL");
-    let video_subsystem = sdl_context
-        .video()
-        .expect("Couldn't get SDL video subsystem");
-
-    let window = video_subsystem
-        .window("A Rust Game", SCREEN_WIDTH, SCREEN_HEIGHT)
-        .position_centered()
-        .position(position_x, position_y)
-        .build()
-        .expect("Failed to build main window");
-
-    let canvas = window
-        .into_canvas()
-        .build()
-        .expect("Failed to get SDL canvas");
+    let mut renderer: Box<dyn Renderer> = if headless {
+        Box::new(NullRenderer)
+    } else {
+        let video_subsystem = sdl_context
+            .video()
+            .expect("Couldn't get SDL video subsystem");
 
-    let mut world = World::new();
+        let window = video_subsystem
+            .window("A Rust Game", SCREEN_WIDTH, SCREEN_HEIGHT)
+            .position_centered()
+            .position(position_x, position_y)
+            .build()
+            .expect("Failed to build main window");
 
-    world.insert_non_send_resource(canvas);
-    world.insert_resource(InputState::default());
-    world.insert_resource(Events::<InputEvent>::default());
-    world.insert_resource(Time::new());
-    insert_mouse_resources(&mut world);
-    world.init_resource::<Camera>();
-    world.insert_resource(Spawn::new());
+        let canvas = window
+            .into_canvas()
+            .build()
+            .expect("Failed to get SDL canvas");
+        Box::new(SdlRenderer::new(canvas))
+    };
 
-    Schedule::new(Startup)
-        .add_systems(init_map_system)
-        .run(&mut world);
+    // REPLAY reproduz um arquivo gravado antes, RECORD_REPLAY grava a partida atual
+    let mut replay = dotenv::var("REPLAY").ok().map(|path| {
+        info!("Replaying inputs from {path}");
+        Replay::load(&path).unwrap_or_else(|err| panic!("Failed to load replay {path}: {err}"))
+    });
+    let mut recorder = dotenv::var("RECORD_REPLAY").ok().map(|path| {
+        info!("Recording inputs to {path}");
+        Recorder::create(&path, REPLAY_DELTA)
+            .unwrap_or_else(|err| panic!("Failed to create replay {path}: {err}"))
+    });
+    let time = match (&replay, &recorder) {
+        (Some(replay), _) => Time::fixed(replay.delta()),
+        (None, Some(_)) => Time::fixed(REPLAY_DELTA),
+        (None, None) => Time::new(),
+    };
 
-    // TODO: Struct com todos os schedulers que roda todos automaticamente
-    // E permite adicionar sistemas usando o nome do  scheduler
-    let mut update_scheduler = Schedule::new(Update);
-    update_scheduler
-        .add_systems((update_input_state, handle_player_input, player_attack).chain())
-        .add_systems(
-            (
-                gravitate,
-                limit_velocity,
-                move_system,
-                handle_bounce_moving_static,
-                handle_collision_moving_static,
-            )
-                .chain()
-                .after(update_input_state),
-        )
-        .add_systems(player_collides_coin)
-        .add_systems(update_jump_time)
-        .add_systems((handle_mouse, insert_mouse_square))
-        .add_systems(player_enter_kill_zone)
-        .add_systems(move_camera.after(handle_player_input));
-
-    let mut render_scheduler = Schedule::new(Render);
-    render_scheduler.add_systems(draw);
+    let bindings =
+        KeyBindings::load(dotenv::var("KEY_BINDINGS").unwrap_or(DEFAULT_BINDINGS_PATH.to_string()));
+    let mut world = init_world(time, bindings);
+    let mut schedules = Schedules::new();
+
+    let mut gamepads = Gamepads::new(
+        sdl_context
+            .game_controller()
+            .expect("Couldn't get SDL game controller subsystem"),
+    );
 
     let mut event_pump = sdl_context
         .event_pump()
         .expect("Failed to get SDL event pump");
+    let mut frame: u64 = 0;
     'running: loop {
-        let mut canvas = world.non_send_resource_mut::<WindowCanvas>();
-        canvas.set_draw_color(Color::RGB(80, 80, 80));
-        canvas.clear();
-        drop(canvas);
         world.resource_mut::<Time>().update();
 
+        let editing = world.resource::<Editor>().active;
+        let mut frame_events = Vec::new();
         for event in event_pump.poll_iter() {
             match event {
                 Event::Quit { .. }
@@ -118,35 +134,287 @@ fn main() -> Result<(), String> {
                     keycode: Some(Keycode::Escape),
                     ..
                 } => break 'running,
-                Event::KeyDown { repeat: false, .. } | Event::KeyUp { repeat: false, .. } => {
-                    if let Ok(input_event) = InputEvent::try_from(event) {
-                        println!("{:?}", input_event);
-                        world.resource_mut::<Events<InputEvent>>().send(input_event);
+                Event::KeyDown {
+                    keycode: Some(key),
+                    keymod,
+                    repeat,
+                    ..
+                } => {
+                    frame_events.push(ReplayEvent::editor_key(key, keymod, repeat));
+                    if !repeat && !editing {
+                        let inputs = world.resource_mut::<KeyBindings>().input_events(&event);
+                        frame_events.extend(inputs.into_iter().map(ReplayEvent::Input));
                     }
                 }
+                // Soltar passa mesmo com o editor aberto, senão o que estava apertado ao abrir
+                // o editor fica preso
+                Event::KeyUp { repeat: false, .. } => {
+                    let inputs = world.resource_mut::<KeyBindings>().input_events(&event);
+                    frame_events.extend(inputs.into_iter().map(ReplayEvent::Input));
+                }
+                event if event.is_controller() => {
+                    let inputs = gamepads.input_events(&event);
+                    frame_events.extend(inputs.into_iter().map(ReplayEvent::Input));
+                }
                 Event::MouseButtonDown { x, y, .. } => {
-                    world
-                        .resource_mut::<Events<MousePress>>()
-                        .send(MousePress::new(x, SCREEN_HEIGHT as i32 - y));
+                    let y = SCREEN_HEIGHT as i32 - y;
+                    frame_events.push(ReplayEvent::MousePress { x, y });
                 }
                 Event::MouseButtonUp { x, y, .. } => {
-                    world
-                        .resource_mut::<Events<MouseLift>>()
-                        .send(MouseLift::new(x, SCREEN_HEIGHT as i32 - y));
+                    let y = SCREEN_HEIGHT as i32 - y;
+                    frame_events.push(ReplayEvent::MouseLift { x, y });
                 }
                 Event::MouseMotion { x, y, .. } => {
-                    println!("motion: ({x},{y})");
+                    let y = SCREEN_HEIGHT as i32 - y;
+                    frame_events.push(ReplayEvent::MouseMotion { x, y });
                 }
                 _ => {}
             }
         }
 
-        update_scheduler.run(&mut world);
-        render_scheduler.run(&mut world);
+        // Durante o replay a entrada do jogador, inclusive o mouse e o editor, é ignorada até
+        // acabar a gravação
+        let mut replay_finished = false;
+        if let Some(playing) = &mut replay {
+            frame_events = playing.events(frame);
+            if playing.finished() {
+                info!("Replay finished at frame {frame}");
+                replay = None;
+                replay_finished = true;
+            }
+        }
+        if let Some(recording) = &mut recorder {
+            if let Err(err) = recording.record(frame, &frame_events) {
+                error!("Failed to record inputs, recording stopped: {err}");
+                recorder = None;
+            }
+        }
+        for event in frame_events {
+            event.send(&mut world);
+        }
+        frame += 1;
 
-        world.non_send_resource_mut::<WindowCanvas>().present();
-        std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
+        schedules.run(&mut world);
+        renderer.present(&world.resource_mut::<DrawCommands>().take());
+
+        if headless {
+            // Sem janela não tem como fechar o jogo, então ele termina junto com o replay
+            if replay_finished {
+                break 'running;
+            }
+        } else {
+            std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
+        }
     }
 
     Ok(())
 }
+
+/// Cria o mundo com todos os recursos e carrega o primeiro mapa
+fn init_world(time: Time, bindings: KeyBindings) -> World {
+    let mut world = World::new();
+
+    world.insert_resource(Screen::new(SCREEN_WIDTH, SCREEN_HEIGHT));
+    world.insert_resource(DrawCommands::default());
+    world.insert_resource(InputState::default());
+    world.insert_resource(bindings);
+    EventRegistry::register_event::<InputEvent>(&mut world);
+    EventRegistry::register_event::<Collision>(&mut world);
+    EventRegistry::register_event::<TriggerEvent>(&mut world);
+    EventRegistry::register_event::<CustomTrigger>(&mut world);
+    EventRegistry::register_event::<LoadMap>(&mut world);
+    EventRegistry::register_event::<Explosion>(&mut world);
+    world.insert_resource(time);
+    insert_mouse_resources(&mut world);
+    world.init_resource::<Camera>();
+    world.insert_resource(Spawn::new());
+    world.insert_resource(CurrentMap::default());
+    world.init_resource::<MapSources>();
+    world.insert_resource(Editor::with_grid(
+        dotenv::var("EDITOR_GRID")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_GRID),
+    ));
+    EventRegistry::register_event::<EditorKey>(&mut world);
+    world.insert_resource(History::default());
+
+    Schedule::new(Startup)
+        .add_systems(init_map_system)
+        .run(&mut world);
+
+    world
+}
+
+/// Todos os schedules que rodam a cada frame, na ordem
+struct Schedules {
+    editor: Schedule,
+    update: Schedule,
+    render: Schedule,
+}
+
+impl Schedules {
+    fn new() -> Self {
+        let mut update = Schedule::new(Update);
+        update
+            .add_systems(
+                (
+                    update_input_state,
+                    update_cursor,
+                    crouch,
+                    handle_player_input,
+                    climb,
+                    swim,
+                    fire_grapple,
+                    wall_slide_and_jump,
+                    use_abilities,
+                    fire_projectiles,
+                    detonate_projectiles,
+                    apply_explosions,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                (
+                    gravitate,
+                    apply_drag,
+                    apply_friction,
+                    limit_velocity,
+                    move_system,
+                    apply_rope,
+                    detect_collisions,
+                    clear_contacts,
+                    handle_bounce_moving_static,
+                    handle_collision_moving_static,
+                    handle_collision_moving_shapes,
+                    handle_collision_moving_moving,
+                )
+                    .chain()
+                    .after(apply_explosions),
+            )
+            .add_systems(player_collides_coin.after(detect_collisions))
+            .add_systems(track_ladders.after(detect_collisions))
+            .add_systems(expire_projectiles.after(detect_collisions))
+            .add_systems(apply_volumes.after(apply_trigger_actions))
+            .add_systems(update_jump_time)
+            // Os eventos duram dois frames do Update, assim quem lê todo frame não perde nenhum.
+            // Com o editor aberto eles esperam, senão o que foi solto no editor se perde
+            .add_systems(event_update_system.before(update_input_state))
+            .add_systems(
+                (
+                    detect_triggers,
+                    apply_trigger_actions,
+                    (load_map_system, log_custom_triggers),
+                )
+                    .chain()
+                    .after(detect_collisions),
+            )
+            .add_systems(move_camera.after(handle_player_input));
+
+        let mut editor = Schedule::new(EditorUpdate);
+        editor.add_systems((handle_mouse, handle_editor_keys, apply_editor_tool).chain());
+
+        let mut render = Schedule::new(Render);
+        render.add_systems((draw, draw_ropes, draw_editor).chain());
+
+        Self {
+            editor,
+            update,
+            render,
+        }
+    }
+
+    /// Roda um frame. Com o editor aberto o [Update] não roda e o jogo fica pausado
+    fn run(&mut self, world: &mut World) {
+        self.editor.run(world);
+        if !world.resource::<Editor>().active {
+            self.update.run(world);
+        }
+        self.render.run(world);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::{
+        abilities::{Abilities, Ability},
+        components::{PhysicsBody, Player, Position, Velocity},
+        input::{Action, ActionState},
+        render::DrawCommand,
+    };
+
+    /// Guarda os frames em vez de desenhar
+    #[derive(Default)]
+    struct RecordingRenderer {
+        frames: Vec<Vec<DrawCommand>>,
+    }
+
+    impl Renderer for RecordingRenderer {
+        fn present(&mut self, commands: &[DrawCommand]) {
+            self.frames.push(commands.to_vec());
+        }
+    }
+
+    #[test]
+    fn runs_headless() {
+        let mut world = init_world(Time::fixed(REPLAY_DELTA), KeyBindings::default());
+        let mut schedules = Schedules::new();
+        let mut renderer = RecordingRenderer::default();
+        let start = world
+            .query_filtered::<&Position, With<Player>>()
+            .single(&world)
+            .clone();
+
+        world
+            .resource_mut::<Events<InputEvent>>()
+            .send(InputEvent::new(Action::Right, ActionState::Active));
+        for _ in 0..30 {
+            world.resource_mut::<Time>().update();
+            schedules.run(&mut world);
+            renderer.present(&world.resource_mut::<DrawCommands>().take());
+        }
+
+        let end = world
+            .query_filtered::<&Position, With<Player>>()
+            .single(&world);
+        assert!(end.x > start.x);
+        assert_eq!(renderer.frames.len(), 30);
+        assert!(renderer.frames.iter().all(|frame| !frame.is_empty()));
+    }
+
+    #[test]
+    fn dash_exceeds_max_speed_until_it_ends() {
+        let mut world = init_world(Time::fixed(REPLAY_DELTA), KeyBindings::default());
+        let mut schedules = Schedules::new();
+        let mut player = world.query_filtered::<(
+            &mut Position,
+            &mut Abilities,
+            &Velocity,
+            &PhysicsBody,
+        ), With<Player>>();
+        let (mut position, mut abilities, _, body) = player.single_mut(&mut world);
+        // Longe do chão, o dash só funciona no ar
+        position.y += 2000.0;
+        abilities.unlock(Ability::AirDash);
+        let (dash_speed, dash_millis) = (abilities.dash_speed, abilities.dash_millis);
+        let max_speed = body.max_horizontal_speed;
+        assert!(dash_speed > max_speed);
+
+        world
+            .resource_mut::<Events<InputEvent>>()
+            .send(InputEvent::new(Action::Dash, ActionState::Active));
+        world.resource_mut::<Time>().update();
+        schedules.run(&mut world);
+        let (.., velocity, _) = player.single(&world);
+        assert!(velocity.x.abs() > max_speed);
+
+        let frames = Duration::from_millis(dash_millis).as_nanos() / REPLAY_DELTA.as_nanos() + 2;
+        for _ in 0..frames {
+            world.resource_mut::<Time>().update();
+            schedules.run(&mut world);
+        }
+        let (.., velocity, _) = player.single(&world);
+        assert!(velocity.x.abs() <= max_speed);
+    }
+}